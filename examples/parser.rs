@@ -16,6 +16,10 @@ fn main() -> Result<(), Box<Report>> {
         .with_logger(Logger::new().with_level(LogLevel::Error))
         .with_cache_directory(".cache");
 
+    config
+        .init_logger()
+        .map_err(|error| Box::new(Report::from(error)))?;
+
     let forest = Parser::new(&config).parse()?;
 
     assert_eq!(forest.source.sources.len(), 3000);
@@ -0,0 +1,104 @@
+use std::env;
+use std::fs;
+use std::time::Duration;
+use std::time::Instant;
+
+use ara_forest::config::Config;
+use ara_forest::logger::LogLevel;
+use ara_forest::logger::Logger;
+use ara_forest::serializer::ArchivedSerializer;
+use ara_forest::serializer::BincodeSerializer;
+use ara_forest::serializer::JsonSerializer;
+use ara_forest::serializer::Serializer;
+use ara_forest::tree::FileStamp;
+use ara_forest::tree::SignedTree;
+use ara_forest::Parser;
+use ara_reporting::Report;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+const ITERATIONS: u32 = 20;
+
+// compares the cost of loading cache entries with each serializer, run it with:
+//
+//   cargo run --example serializers --release -- [project root] [source directory]
+fn main() -> Result<(), Box<Report>> {
+    let mut arguments = env::args().skip(1);
+    let root = arguments
+        .next()
+        .unwrap_or_else(|| format!("{MANIFEST_DIR}/examples/project"));
+    let source = arguments.next().unwrap_or_else(|| "src".to_string());
+
+    let config = Config::new(&root)
+        .with_source(&source)
+        .with_logger(Logger::new().with_level(LogLevel::Error));
+
+    let forest = Parser::new(&config).parse()?;
+    let entries = forest
+        .source
+        .sources
+        .into_iter()
+        .zip(forest.tree.trees)
+        .map(|(source, tree)| {
+            let path = config.root.join(source.origin.as_ref().unwrap());
+            let stamp = FileStamp::from_metadata(&fs::metadata(path).unwrap());
+
+            SignedTree::new(
+                config.hasher.hash(&source.content),
+                // fingerprints aren't checked when measuring.
+                0,
+                &stamp,
+                source.content,
+                tree,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    println!("{} entries, {ITERATIONS} iterations.", entries.len());
+
+    let serializers: [Box<dyn Serializer>; 3] = [
+        Box::new(BincodeSerializer::new()),
+        Box::new(ArchivedSerializer::new()),
+        Box::new(JsonSerializer::new()),
+    ];
+    for serializer in serializers {
+        let data = entries
+            .iter()
+            .map(|entry| serializer.serialize(entry).unwrap())
+            .collect::<Vec<_>>();
+
+        let deserialize = measure(|| {
+            for entry in &data {
+                serializer.deserialize(entry).unwrap();
+            }
+        });
+
+        // what a cache miss costs: finding out the source has changed.
+        let check = measure(|| {
+            for entry in &data {
+                match serializer.header(entry) {
+                    Some(header) => header.signature,
+                    None => serializer.deserialize(entry).unwrap().signature,
+                };
+            }
+        });
+
+        println!(
+            "{:>10}: {} bytes, deserialize {:?}, signature check {:?}",
+            serializer.name(),
+            data.iter().map(Vec::len).sum::<usize>(),
+            deserialize,
+            check,
+        );
+    }
+
+    Ok(())
+}
+
+fn measure<F: Fn()>(f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+
+    start.elapsed() / ITERATIONS
+}
@@ -0,0 +1,244 @@
+use std::fs;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+use tar::Archive;
+use tar::Builder;
+use tar::EntryType;
+use tar::Header;
+
+use crate::config::CacheKey;
+use crate::config::Config;
+use crate::error::Error;
+use crate::ARA_CACHED_SOURCE_EXTENSION;
+use crate::ARA_PARSER_VERSION;
+
+const ARCHIVE_FORMAT_VERSION: &str = "3";
+const ARCHIVE_MANIFEST: &str = "manifest";
+const ARCHIVE_ENTRIES_DIRECTORY: &str = "entries";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheManifest {
+    pub format: String,
+    pub parser: String,
+    pub hasher: String,
+    pub serializer: String,
+    pub cache_key: String,
+}
+
+pub struct CacheArchiver<'a> {
+    config: &'a Config,
+}
+
+impl CacheManifest {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            format: ARCHIVE_FORMAT_VERSION.to_string(),
+            parser: ARA_PARSER_VERSION.to_string(),
+            hasher: config.hasher.name().to_string(),
+            serializer: config.serializer.name().to_string(),
+            cache_key: match config.cache_key {
+                CacheKey::Origin => "origin",
+                CacheKey::Content => "content",
+            }
+            .to_string(),
+        }
+    }
+
+    fn parse(content: &str) -> Result<Self, Error> {
+        let mut manifest = Self {
+            format: String::new(),
+            parser: String::new(),
+            hasher: String::new(),
+            serializer: String::new(),
+            cache_key: String::new(),
+        };
+
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| Error::archive(format!("invalid manifest line `{line}`.")))?;
+            let value = value.trim().to_string();
+            match key.trim() {
+                "format" => manifest.format = value,
+                "parser" => manifest.parser = value,
+                "hasher" => manifest.hasher = value,
+                "serializer" => manifest.serializer = value,
+                "cache-key" => manifest.cache_key = value,
+                key => return Err(Error::archive(format!("unknown manifest key `{key}`."))),
+            }
+        }
+
+        Ok(manifest)
+    }
+}
+
+impl std::fmt::Display for CacheManifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "format: {}", self.format)?;
+        writeln!(f, "parser: {}", self.parser)?;
+        writeln!(f, "hasher: {}", self.hasher)?;
+        writeln!(f, "serializer: {}", self.serializer)?;
+        writeln!(f, "cache-key: {}", self.cache_key)
+    }
+}
+
+impl<'a> CacheArchiver<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self { config }
+    }
+
+    /// Writes every cache entry into a tar archive, along with a manifest describing
+    /// the configuration the entries were produced with.
+    ///
+    /// Returns the number of exported entries.
+    pub fn export<W: Write>(&self, writer: W) -> Result<usize, Error> {
+        let cache = self.get_cache_directory()?;
+
+        let mut builder = Builder::new(writer);
+        let manifest = CacheManifest::from_config(self.config).to_string();
+        let mut header = Header::new_gnu();
+        header.set_size(manifest.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, ARCHIVE_MANIFEST, manifest.as_bytes())?;
+
+        let mut count = 0;
+        for entry in fs::read_dir(cache).map_err(|error| Error::from(error).with_path(cache))? {
+            let entry = entry.map_err(|error| Error::from(error).with_path(cache))?;
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let file_type = entry
+                .file_type()
+                .map_err(|error| Error::from(error).with_path(&path))?;
+            if !file_type.is_file() || !name.ends_with(ARA_CACHED_SOURCE_EXTENSION) {
+                continue;
+            }
+
+            builder
+                .append_path_with_name(&path, Path::new(ARCHIVE_ENTRIES_DIRECTORY).join(&name))
+                .map_err(|error| Error::from(error).with_path(&path))?;
+            count += 1;
+        }
+
+        builder.into_inner()?.flush()?;
+
+        log::info!(
+            event = "cache_exported", cache:% = cache.display(), entries = count;
+            "exported {} cache entries from ({}).",
+            count,
+            cache.display()
+        );
+
+        Ok(count)
+    }
+
+    // errors of the archive itself are attached to its path, so the cache directory is
+    // checked beforehand.
+    pub fn export_to_file(&self, path: &Path) -> Result<usize, Error> {
+        self.get_cache_directory()?;
+
+        let file = fs::File::create(path).map_err(|error| Error::from(error).with_path(path))?;
+
+        self.export(file).map_err(|error| error.with_path(path))
+    }
+
+    /// Extracts the cache entries of an archive into the cache directory.
+    ///
+    /// The archive is rejected if it was produced by an incompatible configuration.
+    ///
+    /// Returns the number of imported entries.
+    pub fn import<R: Read>(&self, reader: R) -> Result<usize, Error> {
+        let cache = self.get_cache_directory()?;
+        fs::create_dir_all(cache).map_err(|error| Error::from(error).with_path(cache))?;
+
+        let mut archive = Archive::new(reader);
+        let mut manifest = None;
+        let mut count = 0;
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.to_path_buf();
+
+            // archives are untrusted input, links and other special files could be used to
+            // write outside the cache directory once the entry is overwritten.
+            if entry.header().entry_type() != EntryType::Regular {
+                return Err(Error::archive(format!(
+                    "archive entry ({}) is not a regular file.",
+                    path.display()
+                )));
+            }
+
+            if path == Path::new(ARCHIVE_MANIFEST) {
+                let mut content = String::new();
+                entry.read_to_string(&mut content)?;
+                manifest = Some(self.check_manifest(CacheManifest::parse(&content)?)?);
+
+                continue;
+            }
+
+            if manifest.is_none() {
+                return Err(Error::archive("the archive must start with a manifest."));
+            }
+
+            // only keep the file name, so entries can't be extracted outside the cache directory.
+            let name = match (path.parent(), path.file_name()) {
+                (Some(parent), Some(name))
+                    if parent == Path::new(ARCHIVE_ENTRIES_DIRECTORY)
+                        && name
+                            .to_string_lossy()
+                            .ends_with(ARA_CACHED_SOURCE_EXTENSION) =>
+                {
+                    name.to_os_string()
+                }
+                _ => {
+                    log::warn!(
+                        event = "archive_entry_skipped";
+                        "skipping unknown archive entry ({}).",
+                        path.display()
+                    );
+
+                    continue;
+                }
+            };
+
+            entry.unpack(cache.join(name))?;
+            count += 1;
+        }
+
+        log::info!(
+            event = "cache_imported", cache:% = cache.display(), entries = count;
+            "imported {} cache entries into ({}).",
+            count,
+            cache.display()
+        );
+
+        Ok(count)
+    }
+
+    pub fn import_from_file(&self, path: &Path) -> Result<usize, Error> {
+        self.get_cache_directory()?;
+
+        let file = fs::File::open(path).map_err(|error| Error::from(error).with_path(path))?;
+
+        self.import(file).map_err(|error| error.with_path(path))
+    }
+
+    fn check_manifest(&self, manifest: CacheManifest) -> Result<CacheManifest, Error> {
+        let expected = CacheManifest::from_config(self.config);
+        if manifest != expected {
+            return Err(Error::archive(format!(
+                "the archive is not compatible with the current configuration.\nexpected:\n{expected}found:\n{manifest}"
+            )));
+        }
+
+        Ok(manifest)
+    }
+
+    fn get_cache_directory(&self) -> Result<&Path, Error> {
+        self.config
+            .cache
+            .as_deref()
+            .ok_or_else(|| Error::archive("a cache directory must be configured."))
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde_json::Value;
+use walkdir::WalkDir;
+
+use crate::config::Config;
+use crate::config::COMPOSER_CONFIG_FILE;
+use crate::error::Error;
+
+const COMPOSER_DEFAULT_VENDOR_DIRECTORY: &str = "vendor";
+const COMPOSER_INSTALLED_FILE: &str = "composer/installed.json";
+
+/// Collects the definitions directories declared by installed Composer packages, in the
+/// `extra.ara.definitions` section of their `composer.json`.
+pub struct VendorDefinitionsCollector<'a> {
+    config: &'a Config,
+}
+
+impl<'a> VendorDefinitionsCollector<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self { config }
+    }
+
+    /// Returns the definitions directories, relative to the project root.
+    pub fn collect(&self) -> Result<Vec<PathBuf>, Error> {
+        let vendor = self.get_vendor_directory()?;
+        if !vendor.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let installed = vendor.join(COMPOSER_INSTALLED_FILE);
+        let packages = if installed.is_file() {
+            self.read_installed_packages(&vendor, &installed)?
+        } else {
+            self.read_package_manifests(&vendor)?
+        };
+
+        let root = normalize(&self.config.root);
+        let mut definitions = Vec::new();
+        for (directory, package) in packages {
+            for definition in self.get_package_definitions(&package) {
+                let path = normalize(&directory.join(definition));
+                let path = path
+                    .strip_prefix(&root)
+                    .map(Path::to_path_buf)
+                    .unwrap_or(path);
+
+                if !definitions.contains(&path) {
+                    definitions.push(path);
+                }
+            }
+        }
+
+        Ok(definitions)
+    }
+
+    fn get_vendor_directory(&self) -> Result<PathBuf, Error> {
+        let composer = self.config.root.join(COMPOSER_CONFIG_FILE);
+        let vendor = if composer.is_file() {
+            read_json(&composer)?
+                .pointer("/config/vendor-dir")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        } else {
+            None
+        };
+
+        Ok(self.config.root.join(
+            vendor
+                .as_deref()
+                .unwrap_or(COMPOSER_DEFAULT_VENDOR_DIRECTORY),
+        ))
+    }
+
+    // composer 2 wraps packages in a `packages` key, while composer 1 lists them directly.
+    fn read_installed_packages(
+        &self,
+        vendor: &Path,
+        installed: &Path,
+    ) -> Result<Vec<(PathBuf, Value)>, Error> {
+        let mut installed = read_json(installed)?;
+        let packages = match installed.get_mut("packages") {
+            Some(packages) => packages.take(),
+            None => installed,
+        };
+
+        let packages = match packages {
+            Value::Array(packages) => packages,
+            _ => {
+                return Err(Error::config("expected a list of packages.")
+                    .with_path(vendor.join(COMPOSER_INSTALLED_FILE)))
+            }
+        };
+
+        Ok(packages
+            .into_iter()
+            .filter_map(|package| {
+                let directory = match package.get("install-path").and_then(Value::as_str) {
+                    Some(path) => vendor.join("composer").join(path),
+                    None => vendor.join(package.get("name")?.as_str()?),
+                };
+
+                Some((directory, package))
+            })
+            .collect())
+    }
+
+    fn read_package_manifests(&self, vendor: &Path) -> Result<Vec<(PathBuf, Value)>, Error> {
+        let mut packages = Vec::new();
+        for entry in WalkDir::new(vendor)
+            .min_depth(2)
+            .max_depth(3)
+            .sort_by_file_name()
+        {
+            let entry = entry?;
+            if !entry.file_type().is_file() || entry.file_name() != COMPOSER_CONFIG_FILE {
+                continue;
+            }
+
+            let directory = entry.path().parent().unwrap().to_path_buf();
+            packages.push((directory, read_json(entry.path())?));
+        }
+
+        Ok(packages)
+    }
+
+    fn get_package_definitions<'b>(&self, package: &'b Value) -> Vec<&'b str> {
+        match package.pointer("/extra/ara/definitions") {
+            Some(Value::String(definition)) => vec![definition.as_str()],
+            Some(Value::Array(definitions)) => {
+                definitions.iter().filter_map(Value::as_str).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn read_json(path: &Path) -> Result<Value, Error> {
+    let content = fs::read(path).map_err(|error| Error::from(error).with_path(path))?;
+
+    serde_json::from_slice(&content)
+        .map_err(|error| Error::config(error.to_string()).with_path(path))
+}
+
+// resolves `..` in install paths such as `vendor/composer/../foo/bar`.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
@@ -1,33 +1,147 @@
+use std::env;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fs;
+use std::fs::OpenOptions;
+use std::path::Component;
+use std::path::Path;
 use std::path::PathBuf;
+use std::process;
+use std::sync::Arc;
 
+use ara_reporting::issue::Issue;
+use ara_reporting::issue::IssueSeverity;
+use ara_reporting::Report;
+use glob::Pattern;
+use serde::Deserialize;
+
+use crate::composer::VendorDefinitionsCollector;
+use crate::error::Error;
 use crate::hash::ContentHasher;
 use crate::hash::FxHasher;
+use crate::logger::LogFormat;
+use crate::logger::LogLevel;
 use crate::logger::Logger;
+use crate::remote::RemoteCache;
+use crate::serializer::ArchivedSerializer;
 use crate::serializer::BincodeSerializer;
+use crate::serializer::CompressedSerializer;
+use crate::serializer::Compression;
+use crate::serializer::JsonSerializer;
 use crate::serializer::Serializer;
 
+pub const ARA_CONFIG_FILE: &str = "ara.toml";
+pub const COMPOSER_CONFIG_FILE: &str = "composer.json";
+
+pub const ENV_CACHE_DIR: &str = "ARA_FOREST_CACHE_DIR";
+pub const ENV_THREADS: &str = "ARA_FOREST_THREADS";
+pub const ENV_LOG: &str = "ARA_FOREST_LOG";
+pub const ENV_LOG_FORMAT: &str = "ARA_FOREST_LOG_FORMAT";
+pub const ENV_NO_CACHE: &str = "ARA_FOREST_NO_CACHE";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheKey {
+    /// Cache entries are named after the hashed origin of the source.
+    Origin,
+    /// Cache entries are named after the hashed content of the source, and the parser version,
+    /// so identical files share the same entry regardless of their location.
+    Content,
+}
+
+#[derive(Clone)]
 pub struct Config {
     pub root: PathBuf,
     pub source: PathBuf,
     pub definitions: Vec<PathBuf>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
     pub cache: Option<PathBuf>,
+    pub cache_key: CacheKey,
+    pub read_only_cache: bool,
+    pub max_cache_size: Option<u64>,
+    pub max_cache_entries: Option<usize>,
+    pub strict_hashing: bool,
+    pub remote: Option<Arc<dyn RemoteCache>>,
     pub threads: usize,
     pub logger: Option<Logger>,
-    pub hasher: Box<dyn ContentHasher>,
-    pub serializer: Box<dyn Serializer>,
+    pub hasher: Arc<dyn ContentHasher>,
+    pub serializer: Arc<dyn Serializer>,
+    pub compression: Compression,
 }
 
 impl Config {
+    /// Loads the configuration of the project located in the directory containing
+    /// the given file.
+    ///
+    /// `composer.json` files are read from their `extra.ara` section, any other file is
+    /// read as TOML.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let root = match path.parent() {
+            Some(parent) if parent != Path::new("") => parent,
+            _ => Path::new("."),
+        };
+
+        let content =
+            fs::read_to_string(path).map_err(|error| Error::from(error).with_path(path))?;
+        let file = if path.file_name() == Some(COMPOSER_CONFIG_FILE.as_ref()) {
+            ConfigFile::from_composer(&content)
+        } else {
+            ConfigFile::from_toml(&content)
+        }
+        .map_err(|error| Error::config(error).with_path(path))?
+        .ok_or_else(|| Error::config("missing `extra.ara` section.").with_path(path))?;
+
+        Self::new(root.to_string_lossy()).with_file(file)
+    }
+
+    /// Loads the configuration from `ara.toml` in the given root, or from the `extra.ara`
+    /// section of its `composer.json`.
+    ///
+    /// Falls back to the default configuration when the project has neither.
+    pub fn discover<R: Into<String>>(root: R) -> Result<Self, Error> {
+        let root = PathBuf::from(root.into());
+
+        let ara = root.join(ARA_CONFIG_FILE);
+        if ara.is_file() {
+            return Self::from_file(ara);
+        }
+
+        let composer = root.join(COMPOSER_CONFIG_FILE);
+        if composer.is_file() {
+            let content = fs::read_to_string(&composer)
+                .map_err(|error| Error::from(error).with_path(&composer))?;
+            let file = ConfigFile::from_composer(&content)
+                .map_err(|error| Error::config(error).with_path(&composer))?;
+
+            if let Some(file) = file {
+                return Self::new(root.to_string_lossy()).with_file(file);
+            }
+        }
+
+        Ok(Self::new(root.to_string_lossy()))
+    }
+
     pub fn new<R: Into<String>>(root: R) -> Self {
         Self {
             root: PathBuf::from(root.into()),
             source: PathBuf::from(String::default()),
             definitions: Vec::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
             cache: None,
+            cache_key: CacheKey::Origin,
+            read_only_cache: false,
+            max_cache_size: None,
+            max_cache_entries: None,
+            strict_hashing: false,
+            remote: None,
             threads: num_cpus::get(),
             logger: None,
-            hasher: Box::new(FxHasher::new()),
-            serializer: Box::new(BincodeSerializer::new()),
+            hasher: Arc::new(FxHasher::new()),
+            serializer: Arc::new(BincodeSerializer::new()),
+            compression: Compression::None,
         }
     }
 
@@ -48,6 +162,38 @@ impl Config {
         self
     }
 
+    /// Appends the definitions directories declared by every installed Composer package,
+    /// in the `extra.ara.definitions` section of its `composer.json`.
+    ///
+    /// Packages are read from `vendor/composer/installed.json`, or from the `composer.json`
+    /// of each package when it doesn't exist.
+    pub fn with_vendor_definitions(mut self) -> Result<Self, Error> {
+        for definition in VendorDefinitionsCollector::new(&self).collect()? {
+            if !self.definitions.contains(&definition) {
+                self.definitions.push(definition);
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Only collect files matching at least one of the given glob patterns, relative to
+    /// the project root.
+    #[must_use]
+    pub fn with_include<P: Into<String>>(mut self, patterns: Vec<P>) -> Self {
+        self.include = patterns.into_iter().map(Into::into).collect();
+
+        self
+    }
+
+    /// Skip files matching any of the given glob patterns, relative to the project root.
+    #[must_use]
+    pub fn with_exclude<P: Into<String>>(mut self, patterns: Vec<P>) -> Self {
+        self.exclude = patterns.into_iter().map(Into::into).collect();
+
+        self
+    }
+
     #[must_use]
     pub fn with_cache_directory<C: Into<String>>(mut self, cache_dir: C) -> Self {
         let path = PathBuf::from(cache_dir.into());
@@ -61,6 +207,85 @@ impl Config {
         self
     }
 
+    #[must_use]
+    pub fn with_cache_key(mut self, cache_key: CacheKey) -> Self {
+        self.cache_key = cache_key;
+
+        self
+    }
+
+    /// Serve hits from the cache directory without ever creating it or writing to it.
+    #[must_use]
+    pub fn with_read_only_cache(mut self, read_only_cache: bool) -> Self {
+        self.read_only_cache = read_only_cache;
+
+        self
+    }
+
+    /// Evict the least recently used cache entries after each run, until the cache
+    /// directory holds at most `max_cache_size` bytes.
+    #[must_use]
+    pub fn with_max_cache_size(mut self, max_cache_size: u64) -> Self {
+        self.max_cache_size = Some(max_cache_size);
+
+        self
+    }
+
+    /// Evict the least recently used cache entries after each run, until the cache
+    /// directory holds at most `max_cache_entries` entries.
+    #[must_use]
+    pub fn with_max_cache_entries(mut self, max_cache_entries: usize) -> Self {
+        self.max_cache_entries = Some(max_cache_entries);
+
+        self
+    }
+
+    pub fn is_cache_limited(&self) -> bool {
+        self.max_cache_size.is_some() || self.max_cache_entries.is_some()
+    }
+
+    /// Always read and hash sources, instead of trusting the size and modification time
+    /// recorded in the cache entry.
+    ///
+    /// The fast path is only available for origin keyed cache entries.
+    #[must_use]
+    pub fn with_strict_hashing(mut self, strict_hashing: bool) -> Self {
+        self.strict_hashing = strict_hashing;
+
+        self
+    }
+
+    #[must_use]
+    pub fn with_serializer<S: Serializer + 'static>(mut self, serializer: S) -> Self {
+        self.serializer = Arc::new(serializer);
+
+        self
+    }
+
+    /// Compress the entries written by the configured serializer, whichever serializer is
+    /// set before or after this call.
+    #[must_use]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+
+        self
+    }
+
+    /// The serializer cache entries are written with, the configured serializer wrapped
+    /// with the configured compression.
+    ///
+    /// Compressed entries are read regardless of the configured compression.
+    pub fn entry_serializer(&self) -> CompressedSerializer {
+        CompressedSerializer::new(self.serializer.clone(), self.compression)
+    }
+
+    #[must_use]
+    pub fn with_remote_cache<R: RemoteCache + 'static>(mut self, remote: R) -> Self {
+        self.remote = Some(Arc::new(remote));
+
+        self
+    }
+
     #[must_use]
     pub fn with_threads(mut self, threads: usize) -> Self {
         self.threads = threads;
@@ -68,10 +293,382 @@ impl Config {
         self
     }
 
+    /// The logger isn't installed by the parser, see `Config::init_logger`.
     #[must_use]
     pub fn with_logger(mut self, logger: Logger) -> Self {
         self.logger = Some(logger);
 
         self
     }
+
+    /// Overrides the configuration with the following environment variables:
+    ///
+    /// - `ARA_FOREST_CACHE_DIR`: the cache directory, relative to the project root.
+    /// - `ARA_FOREST_THREADS`: the number of threads.
+    /// - `ARA_FOREST_LOG`: the log level, one of `off`, `error`, `warn`, `info`, `debug`, `trace`.
+    /// - `ARA_FOREST_LOG_FORMAT`: the log format, one of `text`, `json`.
+    /// - `ARA_FOREST_NO_CACHE`: disables the cache when set to `1` or `true`.
+    ///
+    /// Variables take precedence over every value set before this call, whether it comes
+    /// from a configuration file or a builder method, and are overridden by the ones set after.
+    /// The command line applies them after the configuration file, and before its own options.
+    pub fn with_env_overrides(mut self) -> Result<Self, Error> {
+        if let Some(cache) = read_env(ENV_CACHE_DIR) {
+            self = self.with_cache_directory(cache);
+        }
+
+        if let Some(threads) = read_env(ENV_THREADS) {
+            let threads = threads.parse().map_err(|_| {
+                Error::config(format!(
+                    "{ENV_THREADS} must be a number of threads, `{threads}` given."
+                ))
+            })?;
+
+            self = self.with_threads(threads);
+        }
+
+        if let Some(level) = read_env(ENV_LOG) {
+            let level = level.parse::<LogLevel>()?;
+
+            self.logger = Some(self.logger.take().unwrap_or_default().with_level(level));
+        }
+
+        if let Some(format) = read_env(ENV_LOG_FORMAT) {
+            let format = format.parse::<LogFormat>()?;
+
+            self.logger = Some(self.logger.take().unwrap_or_default().with_format(format));
+        }
+
+        if let Some(no_cache) = read_env(ENV_NO_CACHE) {
+            match no_cache.to_lowercase().as_str() {
+                "1" | "true" => self.cache = None,
+                "0" | "false" => {}
+                _ => {
+                    return Err(Error::config(format!(
+                    "{ENV_NO_CACHE} must be one of `1`, `true`, `0`, `false`, `{no_cache}` given."
+                )))
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Installs the configured logger, if any, see `Logger::init`.
+    pub fn init_logger(&self) -> Result<(), Error> {
+        match &self.logger {
+            Some(logger) => logger.init(),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks the configuration before parsing starts, returning every problem found
+    /// at once.
+    ///
+    /// Warnings are logged, and only reported when the configuration also has errors.
+    pub fn validate(&self) -> Result<(), Box<Report>> {
+        let mut issues = Vec::new();
+
+        if !self.root.is_dir() {
+            issues.push(
+                Issue::from_string(format!(
+                    "project root ({}) is not a directory.",
+                    self.root.display()
+                ))
+                .with_note("the project root must be an existing directory."),
+            );
+        } else {
+            if self.source.as_os_str().is_empty() {
+                issues.push(
+                    Issue::new(
+                        IssueSeverity::Warning,
+                        "no source directory is configured, the whole project root is parsed.",
+                    )
+                    .with_note("set the source directory with `Config::with_source`."),
+                );
+            }
+
+            let mut roots = vec![("source", &self.source)];
+            roots.extend(self.definitions.iter().map(|path| ("definitions", path)));
+            for (kind, path) in &roots {
+                let directory = self.root.join(path);
+                if !directory.is_dir() {
+                    issues.push(
+                        Issue::from_string(format!(
+                            "{} directory ({}) does not exist or is not a directory.",
+                            kind,
+                            directory.display()
+                        ))
+                        .with_note("paths must be relative to the project root directory."),
+                    );
+                }
+            }
+
+            // a source at the project root would flag every definitions directory, parsing
+            // the whole root is already reported above.
+            let roots = roots
+                .iter()
+                .map(|(kind, path)| (*kind, normalize_path(path)))
+                .filter(|(kind, path)| *kind != "source" || !path.as_os_str().is_empty())
+                .collect::<Vec<_>>();
+            for (index, (kind, path)) in roots.iter().enumerate() {
+                for (other_kind, other) in &roots[index + 1..] {
+                    if path.starts_with(other) || other.starts_with(path) {
+                        issues.push(
+                            Issue::from_string(format!(
+                                "{} directory ({}) overlaps with {} directory ({}).",
+                                kind,
+                                display_path(path),
+                                other_kind,
+                                display_path(other)
+                            ))
+                            .with_note("files under both directories would be parsed twice."),
+                        );
+                    }
+                }
+            }
+        }
+
+        if self.threads == 0 {
+            issues.push(
+                Issue::from_string("the number of threads must be at least 1.")
+                    .with_note("leave the number of threads unset to use one thread per cpu."),
+            );
+        }
+
+        for pattern in self.include.iter().chain(&self.exclude) {
+            if let Err(error) = Pattern::new(pattern) {
+                issues.push(Issue::from_string(format!(
+                    "invalid glob pattern `{pattern}`: {error}"
+                )));
+            }
+        }
+
+        if let Some(cache) = &self.cache {
+            if let Some(issue) = self.validate_cache_directory(cache) {
+                issues.push(issue);
+            }
+        }
+
+        if !issues
+            .iter()
+            .any(|issue| issue.severity >= IssueSeverity::Error)
+        {
+            for issue in issues {
+                log::warn!("{}", issue.message);
+            }
+
+            return Ok(());
+        }
+
+        let mut report = Report::new();
+        report.issues = issues;
+
+        Err(Box::new(report))
+    }
+
+    fn validate_cache_directory(&self, cache: &Path) -> Option<Issue> {
+        if cache.exists() && !cache.is_dir() {
+            return Some(Issue::from_string(format!(
+                "cache directory ({}) is not a directory.",
+                cache.display()
+            )));
+        }
+
+        if self.read_only_cache {
+            return None;
+        }
+
+        // the cache directory is created when parsing starts, so check the closest existing
+        // ancestor instead.
+        let existing = cache.ancestors().find(|path| path.exists())?;
+        if existing.is_dir() && is_writable(existing) {
+            return None;
+        }
+
+        Some(
+            Issue::from_string(format!(
+                "cache directory ({}) is not writable.",
+                cache.display()
+            ))
+            .with_note("use a writable directory, or enable the read-only cache."),
+        )
+    }
+
+    fn with_file(mut self, file: ConfigFile) -> Result<Self, Error> {
+        if let Some(source) = file.source {
+            self = self.with_source(source);
+        }
+
+        if !file.definitions.is_empty() {
+            self = self.with_definitions(file.definitions);
+        }
+
+        if file.vendor_definitions {
+            self = self.with_vendor_definitions()?;
+        }
+
+        self = self.with_include(file.include).with_exclude(file.exclude);
+
+        if let Some(cache) = file.cache {
+            self = self.with_cache_directory(cache);
+        }
+
+        if let Some(cache_key) = file.cache_key {
+            self = self.with_cache_key(cache_key);
+        }
+
+        if let Some(threads) = file.threads {
+            self = self.with_threads(threads);
+        }
+
+        match file.hasher.as_deref() {
+            None | Some("fx") => {}
+            Some(hasher) => {
+                return Err(Error::config(format!(
+                    "unknown hasher `{hasher}`, expected `fx`."
+                )))
+            }
+        }
+
+        match file.serializer.as_deref() {
+            None | Some("bincode") => {}
+            Some("archived") => self = self.with_serializer(ArchivedSerializer::new()),
+            Some("json") => self = self.with_serializer(JsonSerializer::new()),
+            Some(serializer) => {
+                return Err(Error::config(format!(
+                "unknown serializer `{serializer}`, expected one of `bincode`, `archived`, `json`."
+            )))
+            }
+        }
+
+        if let Some(logging) = file.logging {
+            let mut logger = Logger::new();
+            if let Some(level) = logging.level {
+                logger = logger.with_level(level);
+            }
+
+            if let Some(format) = logging.format {
+                logger = logger.with_format(format);
+            }
+
+            if let Some(file) = logging.file {
+                logger = logger.with_file(self.root.join(file));
+            }
+
+            self = self.with_logger(logger);
+        }
+
+        Ok(self)
+    }
+}
+
+// hashers, serializers and remote caches are described by their name, or presence.
+impl Debug for Config {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Config")
+            .field("root", &self.root)
+            .field("source", &self.source)
+            .field("definitions", &self.definitions)
+            .field("include", &self.include)
+            .field("exclude", &self.exclude)
+            .field("cache", &self.cache)
+            .field("cache_key", &self.cache_key)
+            .field("read_only_cache", &self.read_only_cache)
+            .field("max_cache_size", &self.max_cache_size)
+            .field("max_cache_entries", &self.max_cache_entries)
+            .field("strict_hashing", &self.strict_hashing)
+            .field("remote", &self.remote.is_some())
+            .field("threads", &self.threads)
+            .field("logger", &self.logger)
+            .field("hasher", &self.hasher.name())
+            .field("serializer", &self.serializer.name())
+            .field("compression", &self.compression)
+            .finish()
+    }
+}
+
+impl From<&Config> for Config {
+    fn from(config: &Config) -> Self {
+        config.clone()
+    }
+}
+
+fn read_env(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+// drops `.` components, so `./src` and `src` compare equal.
+fn normalize_path(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+fn display_path(path: &Path) -> String {
+    if path.as_os_str().is_empty() {
+        return ".".to_string();
+    }
+
+    path.display().to_string()
+}
+
+// permissions don't account for ownership, acls or read-only mounts, creating a file does.
+fn is_writable(directory: &Path) -> bool {
+    let probe = directory.join(format!(".ara-forest-{}.tmp", process::id()));
+    match OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct ConfigFile {
+    source: Option<String>,
+    definitions: Vec<String>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    cache: Option<String>,
+    cache_key: Option<CacheKey>,
+    vendor_definitions: bool,
+    threads: Option<usize>,
+    hasher: Option<String>,
+    serializer: Option<String>,
+    logging: Option<LoggingConfigFile>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LoggingConfigFile {
+    level: Option<LogLevel>,
+    format: Option<LogFormat>,
+    file: Option<PathBuf>,
+}
+
+impl ConfigFile {
+    fn from_toml(content: &str) -> Result<Option<Self>, String> {
+        toml::from_str(content)
+            .map(Some)
+            .map_err(|error| error.to_string())
+    }
+
+    fn from_composer(content: &str) -> Result<Option<Self>, String> {
+        let mut composer: serde_json::Value =
+            serde_json::from_str(content).map_err(|error| error.to_string())?;
+
+        match composer
+            .pointer_mut("/extra/ara")
+            .map(serde_json::Value::take)
+        {
+            Some(section) => serde_json::from_value(section)
+                .map(Some)
+                .map_err(|error| format!("extra.ara: {error}")),
+            None => Ok(None),
+        }
+    }
 }
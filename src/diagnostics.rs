@@ -0,0 +1,517 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::str::FromStr;
+
+use ara_reporting::issue::Issue;
+use ara_reporting::issue::IssueSeverity;
+use ara_reporting::Report;
+use serde::Serialize;
+use serde::Serializer;
+use serde_json::json;
+
+use crate::config::Config;
+use crate::error::Error;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const CHECKSTYLE_VERSION: &str = "4.3";
+// the name diagnostics without a file are reported under, when a file is required.
+const PROJECT_FILE: &str = ".";
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum DiagnosticFormat {
+    /// The human readable report.
+    #[default]
+    Text,
+    /// A JSON array of diagnostics.
+    Json,
+    /// A SARIF 2.1.0 log, as consumed by code scanning tools.
+    Sarif,
+    /// GitHub Actions workflow commands, which annotate the files of a pull request.
+    Github,
+    /// A Checkstyle XML report, listing every source file.
+    Checkstyle,
+    /// A JUnit XML report, with a test case per source file.
+    Junit,
+}
+
+/// An issue of a report, with its location resolved to lines and columns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    #[serde(serialize_with = "serialize_severity")]
+    pub severity: IssueSeverity,
+    pub code: Option<String>,
+    pub message: String,
+    /// The file the issue is located in, relative to the project root.
+    pub file: Option<String>,
+    pub span: Option<DiagnosticSpan>,
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct DiagnosticSpan {
+    /// The byte offset the span starts at.
+    pub from: usize,
+    /// The byte offset the span ends at, exclusive.
+    pub to: usize,
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A 1-based line and column, columns are counted in characters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+pub struct DiagnosticsCollector<'a> {
+    config: &'a Config,
+}
+
+impl<'a> DiagnosticsCollector<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self { config }
+    }
+
+    /// Resolves the issues of a report against the project sources.
+    ///
+    /// Issues without a location, or located in a file that can't be read, have no span.
+    pub fn collect(&self, report: &Report) -> Vec<Diagnostic> {
+        let mut contents = HashMap::new();
+
+        report
+            .issues
+            .iter()
+            .map(|issue| {
+                let location = get_location(issue);
+                let span = location.and_then(|(file, from, to)| {
+                    let content = contents
+                        .entry(file.to_string())
+                        .or_insert_with(|| fs::read_to_string(self.config.root.join(file)).ok());
+
+                    content.as_deref().map(|content| DiagnosticSpan {
+                        from,
+                        to,
+                        start: get_position(content, from),
+                        end: get_position(content, to),
+                    })
+                });
+
+                Diagnostic {
+                    severity: issue.severity,
+                    code: issue.code.clone(),
+                    message: issue.message.clone(),
+                    file: location.map(|(file, _, _)| file.to_string()),
+                    span,
+                    notes: issue.notes.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl DiagnosticFormat {
+    /// Renders the diagnostics of a report, checkstyle and junit also list the parsed
+    /// sources without diagnostics.
+    pub fn render(&self, config: &Config, origins: &[String], report: &Report) -> String {
+        let diagnostics = || DiagnosticsCollector::new(config).collect(report);
+
+        match self {
+            DiagnosticFormat::Text => report.to_string(),
+            DiagnosticFormat::Json => to_json(&diagnostics()),
+            DiagnosticFormat::Sarif => to_sarif(&diagnostics()),
+            DiagnosticFormat::Github => to_github(&diagnostics()),
+            DiagnosticFormat::Checkstyle => {
+                let diagnostics = diagnostics();
+
+                to_checkstyle(&get_files(origins, &diagnostics), &diagnostics)
+            }
+            DiagnosticFormat::Junit => {
+                let diagnostics = diagnostics();
+
+                to_junit(&get_files(origins, &diagnostics), &diagnostics)
+            }
+        }
+    }
+}
+
+impl FromStr for DiagnosticFormat {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "text" => Ok(DiagnosticFormat::Text),
+            "json" => Ok(DiagnosticFormat::Json),
+            "sarif" => Ok(DiagnosticFormat::Sarif),
+            "github" => Ok(DiagnosticFormat::Github),
+            "checkstyle" => Ok(DiagnosticFormat::Checkstyle),
+            "junit" => Ok(DiagnosticFormat::Junit),
+            _ => Err(Error::config(format!(
+                "unknown diagnostic format `{format}`, expected one of `text`, `json`, `sarif`, `github`, `checkstyle`, `junit`."
+            ))),
+        }
+    }
+}
+
+pub fn to_json(diagnostics: &[Diagnostic]) -> String {
+    serde_json::to_string_pretty(diagnostics).unwrap()
+}
+
+pub fn to_sarif(diagnostics: &[Diagnostic]) -> String {
+    let mut rules = diagnostics
+        .iter()
+        .filter_map(|diagnostic| diagnostic.code.as_deref())
+        .collect::<Vec<_>>();
+    rules.sort_unstable();
+    rules.dedup();
+
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut result = json!({
+                "level": get_sarif_level(diagnostic.severity),
+                "message": {
+                    "text": diagnostic.message,
+                },
+            });
+
+            if let Some(code) = &diagnostic.code {
+                result["ruleId"] = json!(code);
+            }
+
+            if let Some(file) = &diagnostic.file {
+                let mut location = json!({
+                    "artifactLocation": {
+                        "uri": file,
+                        "uriBaseId": "%SRCROOT%",
+                    },
+                });
+
+                if let Some(span) = &diagnostic.span {
+                    location["region"] = json!({
+                        "startLine": span.start.line,
+                        "startColumn": span.start.column,
+                        "endLine": span.end.line,
+                        "endColumn": span.end.column,
+                        "byteOffset": span.from,
+                        "byteLength": span.to.saturating_sub(span.from),
+                    });
+                }
+
+                result["locations"] = json!([{ "physicalLocation": location }]);
+            }
+
+            if !diagnostic.notes.is_empty() {
+                result["properties"] = json!({ "notes": diagnostic.notes });
+            }
+
+            result
+        })
+        .collect::<Vec<_>>();
+
+    let sarif = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules
+                        .iter()
+                        .map(|rule| json!({ "id": rule }))
+                        .collect::<Vec<_>>(),
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    });
+
+    serde_json::to_string_pretty(&sarif).unwrap()
+}
+
+/// Formats diagnostics as GitHub Actions workflow commands, one per line.
+pub fn to_github(diagnostics: &[Diagnostic]) -> String {
+    let mut output = String::new();
+    for diagnostic in diagnostics {
+        let command = match diagnostic.severity {
+            IssueSeverity::Error | IssueSeverity::Bug => "error",
+            IssueSeverity::Warning => "warning",
+            IssueSeverity::Note | IssueSeverity::Help => "notice",
+        };
+
+        let mut properties = Vec::new();
+        if let Some(file) = &diagnostic.file {
+            properties.push(format!("file={}", escape_github_property(file)));
+        }
+
+        if let Some(span) = &diagnostic.span {
+            properties.push(format!("line={}", span.start.line));
+            properties.push(format!("col={}", span.start.column));
+            properties.push(format!("endLine={}", span.end.line));
+            properties.push(format!("endColumn={}", span.end.column));
+        }
+
+        if let Some(code) = &diagnostic.code {
+            properties.push(format!("title={}", escape_github_property(code)));
+        }
+
+        output.push_str("::");
+        output.push_str(command);
+        if !properties.is_empty() {
+            output.push(' ');
+            output.push_str(&properties.join(","));
+        }
+        let _ = writeln!(output, "::{}", escape_github_data(&diagnostic.message));
+    }
+
+    output
+}
+
+/// Formats diagnostics as a Checkstyle report, every given file is listed even when it
+/// has no diagnostics.
+pub fn to_checkstyle(files: &[String], diagnostics: &[Diagnostic]) -> String {
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(output, "<checkstyle version=\"{CHECKSTYLE_VERSION}\">");
+
+    for (file, diagnostics) in group_by_file(files, diagnostics) {
+        if diagnostics.is_empty() {
+            let _ = writeln!(output, "  <file name=\"{}\"/>", escape_xml(file));
+
+            continue;
+        }
+
+        let _ = writeln!(output, "  <file name=\"{}\">", escape_xml(file));
+        for diagnostic in diagnostics {
+            let (line, column) = diagnostic
+                .span
+                .map(|span| (span.start.line, span.start.column))
+                .unwrap_or((1, 1));
+            let severity = match diagnostic.severity {
+                IssueSeverity::Error | IssueSeverity::Bug => "error",
+                IssueSeverity::Warning => "warning",
+                IssueSeverity::Note | IssueSeverity::Help => "info",
+            };
+
+            let _ = write!(
+                output,
+                "    <error line=\"{line}\" column=\"{column}\" severity=\"{severity}\" message=\"{}\"",
+                escape_xml(&diagnostic.message)
+            );
+            if let Some(code) = &diagnostic.code {
+                let _ = write!(output, " source=\"{}\"", escape_xml(code));
+            }
+            output.push_str("/>\n");
+        }
+        output.push_str("  </file>\n");
+    }
+
+    output.push_str("</checkstyle>\n");
+
+    output
+}
+
+/// Formats diagnostics as a JUnit report, with a test case per given file.
+///
+/// A test case fails when its file has an error, other diagnostics are written to its output.
+pub fn to_junit(files: &[String], diagnostics: &[Diagnostic]) -> String {
+    let files = group_by_file(files, diagnostics);
+    let failures = files
+        .values()
+        .filter(|diagnostics| diagnostics.iter().any(|diagnostic| is_failure(diagnostic)))
+        .count();
+    let suite = env!("CARGO_PKG_NAME");
+
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        output,
+        "<testsuites name=\"{suite}\" tests=\"{}\" failures=\"{failures}\">",
+        files.len()
+    );
+    let _ = writeln!(
+        output,
+        "  <testsuite name=\"{suite}\" tests=\"{}\" failures=\"{failures}\">",
+        files.len()
+    );
+
+    for (file, diagnostics) in &files {
+        let name = escape_xml(file);
+        if diagnostics.is_empty() {
+            let _ = writeln!(
+                output,
+                "    <testcase name=\"{name}\" classname=\"{suite}\"/>"
+            );
+
+            continue;
+        }
+
+        let _ = writeln!(
+            output,
+            "    <testcase name=\"{name}\" classname=\"{suite}\">"
+        );
+
+        let (errors, others): (Vec<&Diagnostic>, Vec<&Diagnostic>) = diagnostics
+            .iter()
+            .partition(|diagnostic| is_failure(diagnostic));
+        if let Some(first) = errors.first() {
+            let _ = writeln!(
+                output,
+                "      <failure message=\"{}\" type=\"{}\">{}</failure>",
+                escape_xml(&first.message),
+                escape_xml(first.code.as_deref().unwrap_or("error")),
+                escape_xml(&format_junit_lines(file, &errors))
+            );
+        }
+
+        if !others.is_empty() {
+            let _ = writeln!(
+                output,
+                "      <system-out>{}</system-out>",
+                escape_xml(&format_junit_lines(file, &others))
+            );
+        }
+
+        output.push_str("    </testcase>\n");
+    }
+
+    output.push_str("  </testsuite>\n");
+    output.push_str("</testsuites>\n");
+
+    output
+}
+
+// the parsed sources, along with the files diagnostics point at, sorted.
+fn get_files(origins: &[String], diagnostics: &[Diagnostic]) -> Vec<String> {
+    let mut files = origins
+        .iter()
+        .cloned()
+        .chain(
+            diagnostics
+                .iter()
+                .filter_map(|diagnostic| diagnostic.file.clone()),
+        )
+        .collect::<Vec<_>>();
+    files.sort_unstable();
+    files.dedup();
+
+    files
+}
+
+fn group_by_file<'a>(
+    files: &'a [String],
+    diagnostics: &'a [Diagnostic],
+) -> BTreeMap<&'a str, Vec<&'a Diagnostic>> {
+    let mut grouped: BTreeMap<&str, Vec<&Diagnostic>> = files
+        .iter()
+        .map(|file| (file.as_str(), Vec::new()))
+        .collect();
+
+    for diagnostic in diagnostics {
+        grouped
+            .entry(diagnostic.file.as_deref().unwrap_or(PROJECT_FILE))
+            .or_default()
+            .push(diagnostic);
+    }
+
+    grouped
+}
+
+fn is_failure(diagnostic: &Diagnostic) -> bool {
+    diagnostic.severity >= IssueSeverity::Error
+}
+
+fn format_junit_lines(file: &str, diagnostics: &[&Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| match &diagnostic.span {
+            Some(span) => format!(
+                "{}:{}:{}: {}",
+                file, span.start.line, span.start.column, diagnostic.message
+            ),
+            None => format!("{}: {}", file, diagnostic.message),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            character => escaped.push(character),
+        }
+    }
+
+    escaped
+}
+
+fn escape_github_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_github_property(value: &str) -> String {
+    escape_github_data(value)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+// issues point at their source, or at their primary annotation.
+fn get_location(issue: &Issue) -> Option<(&str, usize, usize)> {
+    if let Some((origin, from, to)) = &issue.source {
+        return Some((origin.as_str(), *from, *to));
+    }
+
+    issue
+        .annotations
+        .first()
+        .map(|annotation| (annotation.origin.as_str(), annotation.from, annotation.to))
+}
+
+fn get_position(content: &str, offset: usize) -> Position {
+    let mut offset = offset.min(content.len());
+    while !content.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let before = &content[..offset];
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+
+    Position {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+fn get_sarif_level(severity: IssueSeverity) -> &'static str {
+    match severity {
+        IssueSeverity::Error | IssueSeverity::Bug => "error",
+        IssueSeverity::Warning => "warning",
+        IssueSeverity::Note | IssueSeverity::Help => "note",
+    }
+}
+
+fn serialize_severity<S: Serializer>(
+    severity: &IssueSeverity,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(match severity {
+        IssueSeverity::Note => "note",
+        IssueSeverity::Help => "help",
+        IssueSeverity::Warning => "warning",
+        IssueSeverity::Error => "error",
+        IssueSeverity::Bug => "bug",
+    })
+}
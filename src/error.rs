@@ -1,44 +1,170 @@
-use ara_reporting::issue::Issue;
+use std::path::Path;
+use std::path::PathBuf;
+
 use ara_reporting::Report;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     CacheMiss,
-    SerializeError(String),
-    DeserializeError(String),
-    InvalidPath(String),
-    IoError(std::io::Error),
+    /// The cache entry was produced with another parser version, hasher, serializer
+    /// or source kind.
+    FingerprintMismatch,
+    SerializeError {
+        message: String,
+        path: Option<PathBuf>,
+    },
+    DeserializeError {
+        message: String,
+        path: Option<PathBuf>,
+    },
+    InvalidPath {
+        message: String,
+        path: Option<PathBuf>,
+    },
+    RemoteError {
+        message: String,
+        key: Option<String>,
+    },
+    ArchiveError {
+        message: String,
+        path: Option<PathBuf>,
+    },
+    ConfigError {
+        message: String,
+        path: Option<PathBuf>,
+    },
+    IoError {
+        error: std::io::Error,
+        path: Option<PathBuf>,
+    },
     ParseError(Box<Report>),
     LogError(log::SetLoggerError),
 }
 
+impl Error {
+    pub fn invalid_path<M: Into<String>>(message: M) -> Self {
+        Error::InvalidPath {
+            message: message.into(),
+            path: None,
+        }
+    }
+
+    pub fn remote<M: Into<String>>(message: M) -> Self {
+        Error::RemoteError {
+            message: message.into(),
+            key: None,
+        }
+    }
+
+    pub fn archive<M: Into<String>>(message: M) -> Self {
+        Error::ArchiveError {
+            message: message.into(),
+            path: None,
+        }
+    }
+
+    pub fn config<M: Into<String>>(message: M) -> Self {
+        Error::ConfigError {
+            message: message.into(),
+            path: None,
+        }
+    }
+
+    pub fn serialize<M: Into<String>>(message: M) -> Self {
+        Error::SerializeError {
+            message: message.into(),
+            path: None,
+        }
+    }
+
+    pub fn deserialize<M: Into<String>>(message: M) -> Self {
+        Error::DeserializeError {
+            message: message.into(),
+            path: None,
+        }
+    }
+
+    /// Attaches the file or directory the error relates to, unless it already has one.
+    #[must_use]
+    pub fn with_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        match &mut self {
+            Error::SerializeError { path: target, .. }
+            | Error::DeserializeError { path: target, .. }
+            | Error::InvalidPath { path: target, .. }
+            | Error::ArchiveError { path: target, .. }
+            | Error::ConfigError { path: target, .. }
+            | Error::IoError { path: target, .. }
+                if target.is_none() =>
+            {
+                *target = Some(path.into());
+            }
+            _ => {}
+        }
+
+        self
+    }
+
+    /// Attaches the remote cache key the error relates to, unless it already has one.
+    #[must_use]
+    pub fn with_key<K: Into<String>>(mut self, key: K) -> Self {
+        if let Error::RemoteError { key: target, .. } = &mut self {
+            if target.is_none() {
+                *target = Some(key.into());
+            }
+        }
+
+        self
+    }
+
+    /// The remote cache key the error relates to, if known.
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Error::RemoteError { key, .. } => key.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The file or directory the error relates to, if known.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::SerializeError { path, .. }
+            | Error::DeserializeError { path, .. }
+            | Error::InvalidPath { path, .. }
+            | Error::ArchiveError { path, .. }
+            | Error::ConfigError { path, .. }
+            | Error::IoError { path, .. } => path.as_deref(),
+            _ => None,
+        }
+    }
+}
+
 impl From<walkdir::Error> for Error {
     fn from(error: walkdir::Error) -> Self {
-        Error::IoError(error.into())
+        let path = error.path().map(Path::to_path_buf);
+
+        Error::IoError {
+            error: error.into(),
+            path,
+        }
     }
 }
 
 impl From<std::io::Error> for Error {
     fn from(error: std::io::Error) -> Self {
-        Error::IoError(error)
-    }
-}
-
-impl From<Error> for Report {
-    fn from(error: Error) -> Self {
-        Report::new().with_issue(Issue::from_string(error.to_string()))
+        Error::IoError { error, path: None }
     }
 }
 
 impl From<bincode::error::EncodeError> for Error {
     fn from(error: bincode::error::EncodeError) -> Self {
-        Error::SerializeError(error.to_string())
+        Error::serialize(error.to_string())
     }
 }
 
 impl From<bincode::error::DecodeError> for Error {
     fn from(error: bincode::error::DecodeError) -> Self {
-        Error::DeserializeError(error.to_string())
+        Error::deserialize(error.to_string())
     }
 }
 
@@ -51,13 +177,53 @@ impl From<log::SetLoggerError> for Error {
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::IoError(error) => write!(f, "io error: {error}"),
-            Error::InvalidPath(message) => write!(f, "invalid source: {message}"),
-            Error::SerializeError(message) => write!(f, "serialize error: {message}"),
-            Error::DeserializeError(message) => write!(f, "deserialize error: {message}"),
+            Error::IoError { error, path } => write_with_path(f, "io error", path, error),
+            Error::InvalidPath { message, path } => {
+                write_with_path(f, "invalid source", path, message)
+            }
+            Error::RemoteError { message, key } => match key {
+                Some(key) => write!(f, "remote cache error ({key}): {message}"),
+                None => write!(f, "remote cache error: {message}"),
+            },
+            Error::ArchiveError { message, path } => {
+                write_with_path(f, "archive error", path, message)
+            }
+            Error::ConfigError { message, path } => {
+                write_with_path(f, "config error", path, message)
+            }
+            Error::SerializeError { message, path } => {
+                write_with_path(f, "serialize error", path, message)
+            }
+            Error::DeserializeError { message, path } => {
+                write_with_path(f, "deserialize error", path, message)
+            }
             Error::ParseError(report) => write!(f, "parse error: {report}"),
             Error::LogError(error) => write!(f, "log error: {error}"),
             Error::CacheMiss => write!(f, "cache miss"),
+            Error::FingerprintMismatch => write!(f, "cache miss: fingerprint mismatch"),
+        }
+    }
+}
+
+// `Report` converts from any `std::error::Error`, so errors can still be turned into reports.
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IoError { error, .. } => Some(error),
+            Error::LogError(error) => Some(error),
+            _ => None,
         }
     }
 }
+
+fn write_with_path(
+    f: &mut std::fmt::Formatter<'_>,
+    kind: &str,
+    path: &Option<PathBuf>,
+    message: &dyn std::fmt::Display,
+) -> std::fmt::Result {
+    match path {
+        Some(path) => write!(f, "{kind} ({}): {message}", path.display()),
+        None => write!(f, "{kind}: {message}"),
+    }
+}
@@ -0,0 +1,103 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::config::Config;
+use crate::error::Error;
+use crate::ARA_CACHED_SOURCE_EXTENSION;
+
+pub struct CacheEvictor<'a> {
+    config: &'a Config,
+}
+
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    accessed: SystemTime,
+}
+
+impl<'a> CacheEvictor<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self { config }
+    }
+
+    /// Removes the least recently used cache entries until the cache fits within the
+    /// configured limits, returning the number of removed entries and their total size.
+    pub fn evict(&self) -> Result<(usize, u64), Error> {
+        let mut entries = self.collect_entries()?;
+
+        // most recently used entries first, so everything after the limit is evicted.
+        entries.sort_by_key(|entry| Reverse(entry.accessed));
+
+        let max_entries = self.config.max_cache_entries.unwrap_or(usize::MAX);
+        let max_size = self.config.max_cache_size.unwrap_or(u64::MAX);
+
+        let mut kept_size = 0;
+        let mut full = false;
+        let mut evicted = (0, 0);
+        for (index, entry) in entries.into_iter().enumerate() {
+            full = full || index >= max_entries || kept_size + entry.size > max_size;
+            if !full {
+                kept_size += entry.size;
+
+                continue;
+            }
+
+            // another process sharing the cache may have evicted the entry first.
+            match fs::remove_file(&entry.path) {
+                Ok(()) => {}
+                Err(error) if error.kind() == ErrorKind::NotFound => continue,
+                Err(error) => return Err(Error::from(error).with_path(&entry.path)),
+            }
+            evicted.0 += 1;
+            evicted.1 += entry.size;
+
+            log::info!(
+                event = "cache_evicted", cache:% = entry.path.display();
+                "evicted cache entry ({}).",
+                entry.path.display()
+            );
+        }
+
+        Ok(evicted)
+    }
+
+    fn collect_entries(&self) -> Result<Vec<CacheEntry>, Error> {
+        let mut entries = Vec::new();
+
+        let cache = match &self.config.cache {
+            Some(cache) if cache.is_dir() => cache,
+            _ => return Ok(entries),
+        };
+
+        for entry in fs::read_dir(cache).map_err(|error| Error::from(error).with_path(cache))? {
+            let entry = entry.map_err(|error| Error::from(error).with_path(cache))?;
+            let path = entry.path();
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(error) if error.kind() == ErrorKind::NotFound => continue,
+                Err(error) => return Err(Error::from(error).with_path(&path)),
+            };
+            if !metadata.is_file()
+                || !entry
+                    .file_name()
+                    .to_string_lossy()
+                    .ends_with(ARA_CACHED_SOURCE_EXTENSION)
+            {
+                continue;
+            }
+
+            entries.push(CacheEntry {
+                size: metadata.len(),
+                accessed: metadata
+                    .modified()
+                    .map_err(|error| Error::from(error).with_path(&path))?,
+                path,
+            });
+        }
+
+        Ok(entries)
+    }
+}
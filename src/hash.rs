@@ -1,6 +1,7 @@
 use std::hash::Hasher;
 
 pub trait ContentHasher: Send + Sync {
+    fn name(&self) -> &str;
     fn hash(&self, content: &str) -> u64;
 }
 
@@ -13,6 +14,10 @@ impl FxHasher {
 }
 
 impl ContentHasher for FxHasher {
+    fn name(&self) -> &str {
+        "fx"
+    }
+
     fn hash(&self, content: &str) -> u64 {
         let mut hasher = rustc_hash::FxHasher::default();
         hasher.write(content.as_bytes());
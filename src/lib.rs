@@ -1,108 +1,341 @@
 use std::fs;
+use std::path::Path;
 use std::path::PathBuf;
 use std::thread;
+use std::time::Instant;
 
-use ara_parser::tree::Tree;
 use ara_parser::tree::TreeMap;
 use ara_reporting::Report;
-use ara_source::source::Source;
 use ara_source::SourceMap;
 
 use crate::config::Config;
 use crate::error::Error;
+use crate::eviction::CacheEvictor;
 use crate::source::SourceFilesCollector;
+use crate::stats::Stats;
+use crate::symbol::SymbolIndex;
 use crate::tree::TreeBuilder;
 
+pub mod archive;
+pub mod composer;
 pub mod config;
+pub mod diagnostics;
 pub mod error;
+pub(crate) mod eviction;
 pub(crate) mod hash;
 pub mod logger;
-pub(crate) mod serializer;
+pub mod remote;
+pub mod serializer;
 pub mod source;
-pub(crate) mod tree;
+pub mod stats;
+pub mod symbol;
+pub mod tree;
+pub mod verify;
 
 pub(crate) const ARA_SOURCE_EXTENSION: &str = "ara";
 pub(crate) const ARA_DEFINITION_EXTENSION: &str = "d.ara";
 pub(crate) const ARA_CACHED_SOURCE_EXTENSION: &str = "ara.cache";
+// keep in sync with the `ara_parser` requirement in Cargo.toml.
+pub(crate) const ARA_PARSER_VERSION: &str = "0.6.6";
 
 #[derive(Debug)]
 pub struct Forest {
     pub source: SourceMap,
     pub tree: TreeMap,
+    pub symbols: SymbolIndex,
+    pub stats: Stats,
 }
 
 impl Forest {
     pub fn new(source: SourceMap, tree: TreeMap) -> Self {
-        Self { source, tree }
+        Self {
+            source,
+            tree,
+            symbols: SymbolIndex::new(),
+            stats: Stats::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_symbols(mut self, symbols: SymbolIndex) -> Self {
+        self.symbols = symbols;
+
+        self
+    }
+
+    #[must_use]
+    pub fn with_stats(mut self, stats: Stats) -> Self {
+        self.stats = stats;
+
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct CacheWarmup {
+    pub stats: Stats,
+    pub report: Report,
+    /// The origin of every parsed source, relative to the project root.
+    pub origins: Vec<String>,
+}
+
+impl CacheWarmup {
+    pub fn new(stats: Stats, report: Report) -> Self {
+        Self {
+            stats,
+            report,
+            origins: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_origins(mut self, origins: Vec<String>) -> Self {
+        self.origins = origins;
+
+        self
+    }
+}
+
+/// The parse errors of every source, along with the sources that were parsed.
+#[derive(Debug)]
+pub struct Diagnosis {
+    pub stats: Stats,
+    pub report: Report,
+    /// The origin of every parsed source, relative to the project root.
+    pub origins: Vec<String>,
+}
+
+impl Diagnosis {
+    pub fn new(stats: Stats, report: Report, origins: Vec<String>) -> Self {
+        Self {
+            stats,
+            report,
+            origins,
+        }
     }
 }
 
-pub struct Parser<'a> {
-    pub config: &'a Config,
-    tree_builder: TreeBuilder<'a>,
+pub struct Parser {
+    pub config: Config,
 }
 
-impl<'a> Parser<'a> {
-    pub fn new(config: &'a Config) -> Self {
+impl Parser {
+    /// Creates a parser owning its configuration, references are cloned.
+    pub fn new<C: Into<Config>>(config: C) -> Self {
         Parser {
-            config,
-            tree_builder: TreeBuilder::new(config),
+            config: config.into(),
         }
     }
 
+    /// Parses every source, or returns the parse errors of every source that failed.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "parse",
+            skip_all,
+            fields(root = %self.config.root.display(), files)
+        )
+    )]
     pub fn parse(&self) -> Result<Forest, Box<Report>> {
-        self.init_logger().map_err(|error| Box::new(error.into()))?;
+        let (results, stats) =
+            self.build(|builder, source_path| match builder.build(source_path) {
+                Ok(source_tree) => Ok(Some(Ok(source_tree))),
+                Err(Error::ParseError(report)) => Ok(Some(Err(report))),
+                Err(error) => Err(Box::new(error.into())),
+            })?;
+
+        let mut report = Report::new();
+        let mut sources = Vec::with_capacity(results.len());
+        let mut trees = Vec::with_capacity(results.len());
+        let mut index = SymbolIndex::new();
+        for result in results {
+            match result {
+                Ok((source, tree, symbols)) => {
+                    index.insert(&tree.source, source.kind, &symbols);
+                    sources.push(source);
+                    trees.push(tree);
+                }
+                Err(mut failure) => report.issues.append(&mut failure.issues),
+            }
+        }
 
-        let (sources, trees) =
-            thread::scope(|scope| -> Result<(Vec<Source>, Vec<Tree>), Box<Report>> {
-                self.create_cache_dir()
-                    .map_err(|error| Box::new(error.into()))?;
+        if !report.issues.is_empty() {
+            return Err(Box::new(report));
+        }
 
-                let files = SourceFilesCollector::new(self.config)
-                    .collect()
-                    .map_err(|error| Box::new(error.into()))?;
+        Ok(Forest::new(SourceMap::new(sources), TreeMap::new(trees))
+            .with_symbols(index)
+            .with_stats(stats))
+    }
 
-                if files.is_empty() {
-                    return Ok((Vec::new(), Vec::new()));
-                }
+    /// Parses every source and writes the cache entries, without keeping the sources
+    /// and trees in memory.
+    ///
+    /// Parse errors don't stop the warmup, they are collected into the returned report instead.
+    /// A writable cache directory, or a remote cache, must be configured.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "warm_cache",
+            skip_all,
+            fields(root = %self.config.root.display(), files)
+        )
+    )]
+    pub fn warm_cache(&self) -> Result<CacheWarmup, Box<Report>> {
+        if self.config.remote.is_none() {
+            if self.config.cache.is_none() {
+                return Err(Box::new(
+                    Error::config("a cache directory must be configured.").into(),
+                ));
+            }
 
-                let threads_count = self.threads_count(files.len());
-                let chunks = files
-                    .chunks(files.len() / threads_count)
-                    .map(Vec::from)
-                    .collect::<Vec<Vec<PathBuf>>>();
-
-                let mut threads = Vec::with_capacity(threads_count);
-                for chunk in chunks.into_iter() {
-                    threads.push(scope.spawn(
-                        move || -> Result<Vec<(Source, Tree)>, Box<Report>> {
-                            let mut source_tree = Vec::with_capacity(chunk.len());
-                            for source_path in chunk {
-                                let (source, tree) = self
-                                    .tree_builder
-                                    .build(&source_path)
-                                    .map_err(|error| match error {
-                                        Error::ParseError(report) => report,
-                                        _ => Box::new(error.into()),
-                                    })?;
-                                source_tree.push((source, tree));
+            if self.config.read_only_cache {
+                return Err(Box::new(
+                    Error::config("a read-only cache can't be warmed.").into(),
+                ));
+            }
+        }
+
+        let (results, stats) = self.build(|builder, source_path| {
+            let origin = builder.strip_root(source_path);
+
+            match builder.warm(source_path) {
+                Ok(()) => Ok(Some((origin, None))),
+                Err(Error::ParseError(report)) => Ok(Some((origin, Some(report)))),
+                Err(error) => Err(Box::new(error.into())),
+            }
+        })?;
+
+        let (origins, report) = merge_reports(results);
+
+        Ok(CacheWarmup::new(stats, report).with_origins(origins))
+    }
+
+    /// Parses every source without keeping the sources and trees in memory, collecting
+    /// the parse errors of every source instead of stopping at the first one.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "diagnose",
+            skip_all,
+            fields(root = %self.config.root.display(), files)
+        )
+    )]
+    pub fn diagnose(&self) -> Result<Diagnosis, Box<Report>> {
+        let (results, stats) = self.build(|builder, source_path| {
+            let origin = builder.strip_root(source_path);
+
+            match builder.build(source_path) {
+                Ok(_) => Ok(Some((origin, None))),
+                Err(Error::ParseError(report)) => Ok(Some((origin, Some(report)))),
+                Err(error) => Err(Box::new(error.into())),
+            }
+        })?;
+
+        let (origins, report) = merge_reports(results);
+
+        Ok(Diagnosis::new(stats, report, origins))
+    }
+
+    fn build<T, F>(&self, handle: F) -> Result<(Vec<T>, Stats), Box<Report>>
+    where
+        T: Send,
+        F: Fn(&mut TreeBuilder, &Path) -> Result<Option<T>, Box<Report>> + Sync,
+    {
+        self.config.validate()?;
+
+        if let Some(remote) = &self.config.remote {
+            remote.reset();
+        }
+
+        let mut stats = Stats::new();
+        let result = thread::scope(|scope| -> Result<Vec<T>, Box<Report>> {
+            self.create_cache_dir()
+                .map_err(|error| Box::new(error.into()))?;
+
+            let start = Instant::now();
+            let files = SourceFilesCollector::new(&self.config)
+                .collect()
+                .map_err(|error| Box::new(error.into()))?;
+            stats.collect_duration = start.elapsed();
+
+            if files.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let start = Instant::now();
+
+            let threads_count = self.threads_count(files.len());
+            let chunks = files
+                .chunks(files.len() / threads_count)
+                .map(Vec::from)
+                .collect::<Vec<Vec<PathBuf>>>();
+
+            let handle = &handle;
+            let mut threads = Vec::with_capacity(threads_count);
+            for chunk in chunks.into_iter() {
+                // spans aren't inherited by spawned threads, so the chunk span is created here.
+                #[cfg(feature = "tracing")]
+                let span = tracing::info_span!("chunk", files = chunk.len());
+
+                threads.push(
+                    scope.spawn(move || -> Result<(Vec<T>, Stats), Box<Report>> {
+                        #[cfg(feature = "tracing")]
+                        let _span = span.enter();
+
+                        let mut tree_builder = TreeBuilder::new(&self.config);
+                        let mut result = Vec::with_capacity(chunk.len());
+                        for source_path in chunk {
+                            if let Some(item) = handle(&mut tree_builder, &source_path)? {
+                                result.push(item);
                             }
+                        }
 
-                            Ok(source_tree)
-                        },
-                    ));
-                }
+                        Ok((result, *tree_builder.stats()))
+                    }),
+                );
+            }
 
-                let mut result = Vec::new();
-                for handle in threads {
-                    result.extend(handle.join().unwrap()?);
-                }
-                let (sources, trees) = result.into_iter().unzip();
+            let mut result = Vec::new();
+            for thread in threads {
+                let (items, thread_stats) = thread.join().unwrap()?;
+                result.extend(items);
+                stats.merge(&thread_stats);
+            }
+            stats.build_duration = start.elapsed();
 
-                Ok((sources, trees))
-            })?;
+            Ok(result)
+        })?;
 
-        Ok(Forest::new(SourceMap::new(sources), TreeMap::new(trees)))
+        if self.config.is_cache_limited() && !self.config.read_only_cache {
+            // eviction is housekeeping, failing to evict doesn't discard the parsed trees.
+            match CacheEvictor::new(&self.config).evict() {
+                Ok(evicted) => (stats.evicted_entries, stats.evicted_bytes) = evicted,
+                Err(error) => log::warn!(
+                    event = "cache_eviction_error";
+                    "failed to evict cache entries: {}",
+                    error
+                ),
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("files", stats.files);
+
+        log::info!(
+            event = "parsed",
+            files = stats.files,
+            cache_hits = stats.cache_hits,
+            remote_cache_hits = stats.remote_cache_hits,
+            cache_misses = stats.cache_misses();
+            "parsed {} files ({} cache hits, {} remote cache hits, {} cache misses).",
+            stats.files,
+            stats.cache_hits,
+            stats.remote_cache_hits,
+            stats.cache_misses(),
+        );
+
+        Ok((result, stats))
     }
 
     fn threads_count(&self, files_len: usize) -> usize {
@@ -114,18 +347,34 @@ impl<'a> Parser<'a> {
     }
 
     fn create_cache_dir(&self) -> Result<(), Error> {
-        if self.config.cache.is_some() {
-            fs::create_dir_all(self.config.cache.as_ref().unwrap())?;
+        match &self.config.cache {
+            Some(cache) if !self.config.read_only_cache => {
+                fs::create_dir_all(cache).map_err(|error| Error::from(error).with_path(cache))?
+            }
+            Some(cache) if !cache.is_dir() => log::warn!(
+                event = "cache_missing", cache:% = cache.display();
+                "read-only cache directory ({}) does not exist, parsing without cache.",
+                cache.display(),
+            ),
+            _ => {}
         }
 
         Ok(())
     }
+}
 
-    fn init_logger(&self) -> Result<(), Error> {
-        if self.config.logger.is_some() {
-            self.config.logger.as_ref().unwrap().init()?
+// merges the reports of every parsed source, returning the sorted origins of the sources.
+fn merge_reports(results: Vec<(String, Option<Box<Report>>)>) -> (Vec<String>, Report) {
+    let mut report = Report::new();
+    let mut origins = Vec::with_capacity(results.len());
+    for (origin, failure) in results {
+        if let Some(mut failure) = failure {
+            report.issues.append(&mut failure.issues);
         }
 
-        Ok(())
+        origins.push(origin);
     }
+    origins.sort_unstable();
+
+    (origins, report)
 }
@@ -1,12 +1,29 @@
+use log::kv::Key;
+use log::kv::Value;
+use log::kv::VisitSource;
+use log::Log;
+use log::Metadata;
+use log::Record;
+use serde::Deserialize;
+use serde_json::Map;
 use simplelog::*;
 use std::fmt::Debug;
 use std::fs::File;
+use std::io::Write;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use crate::error::Error;
 
+// whether `Logger::init` already installed the global logger.
+static INSTALLED: Mutex<bool> = Mutex::new(false);
+
 #[repr(usize)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LogLevel {
     Off,
     Error,
@@ -16,20 +33,48 @@ pub enum LogLevel {
     Trace,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable lines, colored on terminals.
+    #[default]
+    Text,
+    /// One JSON object per line, with the `timestamp`, `level`, `event`, `origin`, `cache`
+    /// and `message` fields always present.
+    Json,
+}
+
+#[derive(Debug, Clone)]
 pub struct Logger {
     level: Option<LogLevel>,
+    format: LogFormat,
     file: Option<PathBuf>,
 }
 
+struct JsonLogger {
+    level: LevelFilter,
+    config: Config,
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+struct JsonFields(Map<String, serde_json::Value>);
+
 impl Logger {
     pub fn new() -> Self {
         Self {
             level: None,
+            format: LogFormat::Text,
             file: None,
         }
     }
 
+    #[must_use]
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+
+        self
+    }
+
     #[must_use]
     pub fn with_level(mut self, level: LogLevel) -> Self {
         self.level = Some(level);
@@ -44,25 +89,39 @@ impl Logger {
         self
     }
 
+    /// Installs the logger as the global `log` logger.
+    ///
+    /// The parser never installs it, applications embedding the library are expected to
+    /// set up their own logger instead. Calling this more than once is a no-op, but it fails
+    /// when another logger was already installed.
     pub fn init(&self) -> Result<(), Error> {
-        let level = self.level.unwrap_or(LogLevel::Off);
+        let mut installed = INSTALLED.lock().unwrap_or_else(|error| error.into_inner());
+        if *installed {
+            return Ok(());
+        }
 
-        let mut loggers: Vec<Box<dyn SharedLogger>> = vec![TermLogger::new(
-            level.into(),
-            Config::default(),
-            TerminalMode::Mixed,
-            ColorChoice::Auto,
-        )];
+        let level = self.level.unwrap_or(LogLevel::Off);
 
-        if self.file.is_some() {
-            loggers.push(WriteLogger::new(
+        let mut loggers: Vec<Box<dyn SharedLogger>> = match self.format {
+            LogFormat::Text => vec![TermLogger::new(
                 level.into(),
                 Config::default(),
-                File::create(self.file.as_ref().unwrap())?,
-            ));
+                TerminalMode::Mixed,
+                ColorChoice::Auto,
+            )],
+            LogFormat::Json => vec![JsonLogger::new(level.into(), std::io::stderr())],
+        };
+
+        if let Some(file) = &self.file {
+            let file = File::create(file).map_err(|error| Error::from(error).with_path(file))?;
+            loggers.push(match self.format {
+                LogFormat::Text => WriteLogger::new(level.into(), Config::default(), file),
+                LogFormat::Json => JsonLogger::new(level.into(), file),
+            });
         }
 
         CombinedLogger::init(loggers)?;
+        *installed = true;
 
         Ok(())
     }
@@ -74,6 +133,154 @@ impl Default for Logger {
     }
 }
 
+impl JsonLogger {
+    fn new<W: Write + Send + 'static>(level: LevelFilter, writer: W) -> Box<Self> {
+        Box::new(Self {
+            level,
+            config: Config::default(),
+            writer: Mutex::new(Box::new(writer)),
+        })
+    }
+
+    fn format(&self, record: &Record<'_>) -> String {
+        let mut fields = JsonFields(Map::new());
+        for field in ["event", "origin", "cache"] {
+            fields.0.insert(field.to_string(), serde_json::Value::Null);
+        }
+
+        let _ = record.key_values().visit(&mut fields);
+
+        let mut fields = fields.0;
+        fields.insert(
+            "timestamp".to_string(),
+            format_timestamp(SystemTime::now()).into(),
+        );
+        fields.insert(
+            "level".to_string(),
+            record.level().as_str().to_lowercase().into(),
+        );
+        fields.insert("message".to_string(), record.args().to_string().into());
+
+        serde_json::Value::Object(fields).to_string()
+    }
+}
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = self.format(record);
+        let mut writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let _ = writeln!(writer, "{line}");
+    }
+
+    fn flush(&self) {
+        let mut writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let _ = writer.flush();
+    }
+}
+
+impl SharedLogger for JsonLogger {
+    fn level(&self) -> LevelFilter {
+        self.level
+    }
+
+    fn config(&self) -> Option<&Config> {
+        Some(&self.config)
+    }
+
+    fn as_log(self: Box<Self>) -> Box<dyn Log> {
+        self
+    }
+}
+
+impl<'kvs> VisitSource<'kvs> for JsonFields {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        let value = match value.to_u64() {
+            Some(number) => number.into(),
+            None => value.to_string().into(),
+        };
+
+        self.0.insert(key.to_string(), value);
+
+        Ok(())
+    }
+}
+
+// formats the time as RFC 3339 in UTC, e.g. `2023-02-01T12:30:00.000Z`.
+fn format_timestamp(time: SystemTime) -> String {
+    let duration = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = duration.as_secs();
+    let (days, seconds) = (seconds / 86_400, seconds % 86_400);
+
+    // converts days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = days as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60,
+        duration.subsec_millis(),
+    )
+}
+
+impl FromStr for LogLevel {
+    type Err = Error;
+
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level.to_lowercase().as_str() {
+            "off" => Ok(LogLevel::Off),
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => Err(Error::config(format!(
+                "unknown log level `{level}`, expected one of `off`, `error`, `warn`, `info`, `debug`, `trace`."
+            ))),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(Error::config(format!(
+                "unknown log format `{format}`, expected one of `text`, `json`."
+            ))),
+        }
+    }
+}
+
 impl From<LogLevel> for LevelFilter {
     fn from(level: LogLevel) -> Self {
         match level {
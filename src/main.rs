@@ -0,0 +1,346 @@
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use ara_reporting::Report;
+use ara_source::source::Source;
+
+use clap::Args;
+use clap::Parser as ClapParser;
+use clap::Subcommand;
+
+use ara_forest::archive::CacheArchiver;
+use ara_forest::config::Config;
+use ara_forest::diagnostics::DiagnosticFormat;
+use ara_forest::error::Error;
+use ara_forest::tree::get_source_kind;
+use ara_forest::verify::CacheVerifier;
+use ara_forest::Parser;
+
+#[derive(ClapParser)]
+#[command(name = "ara-forest", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Parse a project, populating its cache.
+    Parse {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// Print cache statistics once parsing is done.
+        #[arg(long)]
+        stats: bool,
+
+        /// The diagnostics format, one of `text`, `json`, `sarif`, `github`, `checkstyle`, `junit`.
+        #[arg(long, default_value = "text")]
+        format: DiagnosticFormat,
+    },
+    /// Populate the cache of a project, without keeping the parsed trees around.
+    Warm {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// Print cache statistics once warming is done.
+        #[arg(long)]
+        stats: bool,
+
+        /// The diagnostics format, one of `text`, `json`, `sarif`, `github`, `checkstyle`, `junit`.
+        #[arg(long, default_value = "text")]
+        format: DiagnosticFormat,
+    },
+    /// Verify every entry of a project cache.
+    Verify {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// Remove corrupt, stale and foreign entries.
+        #[arg(long)]
+        repair: bool,
+    },
+    /// Print the tree of a source file as JSON.
+    Dump {
+        /// The source file to parse.
+        file: PathBuf,
+    },
+    /// Bundle a project cache into a portable archive.
+    Export {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// The archive file to write.
+        #[arg(long)]
+        archive: PathBuf,
+    },
+    /// Import a portable archive into a project cache.
+    Import {
+        #[command(flatten)]
+        project: ProjectArgs,
+
+        /// The archive file to read.
+        #[arg(long)]
+        archive: PathBuf,
+    },
+}
+
+#[derive(Args)]
+struct ProjectArgs {
+    /// The project root directory.
+    #[arg(default_value = ".")]
+    root: String,
+
+    /// The configuration file, defaults to `ara.toml` or `composer.json` in the project root.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// The source directory, relative to the project root.
+    #[arg(long)]
+    source: Option<String>,
+
+    /// A definitions directory, relative to the project root.
+    #[arg(long = "definitions")]
+    definitions: Vec<String>,
+
+    /// Also parse the definitions declared by installed Composer packages.
+    #[arg(long)]
+    vendor_definitions: bool,
+
+    /// The cache directory, relative to the project root.
+    #[arg(long)]
+    cache: Option<String>,
+
+    /// The number of threads used for parsing.
+    #[arg(long)]
+    threads: Option<usize>,
+}
+
+impl ProjectArgs {
+    // options given on the command line take precedence over environment variables, which
+    // take precedence over the configuration file.
+    fn config(&self) -> Result<Config, Error> {
+        let mut config = match &self.config {
+            Some(file) => Config::from_file(file)?,
+            None => Config::discover(&self.root)?,
+        }
+        .with_env_overrides()?;
+
+        if let Some(source) = &self.source {
+            config = config.with_source(source);
+        }
+
+        if !self.definitions.is_empty() {
+            config = config.with_definitions(self.definitions.clone());
+        }
+
+        if self.vendor_definitions {
+            config = config.with_vendor_definitions()?;
+        }
+
+        if let Some(cache) = &self.cache {
+            config = config.with_cache_directory(cache);
+        }
+
+        if let Some(threads) = self.threads {
+            config = config.with_threads(threads);
+        }
+
+        Ok(config)
+    }
+}
+
+fn main() -> ExitCode {
+    match Cli::parse().command {
+        Command::Parse {
+            project,
+            stats,
+            format,
+        } => run(&project, |config| parse(config, stats, format)),
+        Command::Warm {
+            project,
+            stats,
+            format,
+        } => run(&project, |config| warm(config, stats, format)),
+        Command::Verify { project, repair } => run(&project, |config| verify(config, repair)),
+        Command::Dump { file } => dump(&file),
+        Command::Export { project, archive } => run(&project, |config| {
+            report(
+                CacheArchiver::new(config).export_to_file(&archive),
+                "exported",
+            )
+        }),
+        Command::Import { project, archive } => run(&project, |config| {
+            report(
+                CacheArchiver::new(config).import_from_file(&archive),
+                "imported",
+            )
+        }),
+    }
+}
+
+fn run<F: FnOnce(&Config) -> ExitCode>(project: &ProjectArgs, command: F) -> ExitCode {
+    match project.config().and_then(|config| {
+        config.init_logger()?;
+
+        Ok(config)
+    }) {
+        Ok(config) => command(&config),
+        Err(error) => {
+            eprintln!("{error}");
+
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse(config: &Config, print_stats: bool, format: DiagnosticFormat) -> ExitCode {
+    let diagnosis = match Parser::new(config).diagnose() {
+        Ok(diagnosis) => diagnosis,
+        Err(report) => return diagnose(config, &[], &report, format),
+    };
+
+    if print_stats {
+        print_output(&diagnosis.stats, format);
+    }
+
+    diagnose(config, &diagnosis.origins, &diagnosis.report, format)
+}
+
+fn warm(config: &Config, print_stats: bool, format: DiagnosticFormat) -> ExitCode {
+    let warmup = match Parser::new(config).warm_cache() {
+        Ok(warmup) => warmup,
+        Err(report) => return diagnose(config, &[], &report, format),
+    };
+
+    if print_stats {
+        print_output(&warmup.stats, format);
+    }
+
+    diagnose(config, &warmup.origins, &warmup.report, format)
+}
+
+// machine readable diagnostics are always printed to stdout, even when there are none, so
+// every other output goes to stderr to keep them parsable.
+fn diagnose(
+    config: &Config,
+    origins: &[String],
+    report: &Report,
+    format: DiagnosticFormat,
+) -> ExitCode {
+    match format {
+        DiagnosticFormat::Text if report.issues.is_empty() => {}
+        DiagnosticFormat::Text => eprintln!("{report}"),
+        _ => println!("{}", format.render(config, origins, report).trim_end()),
+    }
+
+    if report.issues.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn print_output<T: std::fmt::Display>(output: &T, format: DiagnosticFormat) {
+    match format {
+        DiagnosticFormat::Text => println!("{output}"),
+        _ => eprintln!("{output}"),
+    }
+}
+
+fn verify(config: &Config, repair: bool) -> ExitCode {
+    if config.cache.is_none() {
+        eprintln!("a cache directory is required to verify the cache.");
+
+        return ExitCode::FAILURE;
+    }
+
+    let verifier = CacheVerifier::new(config);
+    let result = if repair {
+        verifier.repair()
+    } else {
+        verifier.verify()
+    };
+
+    match result {
+        Ok(verification) => {
+            for path in &verification.corrupt {
+                println!("corrupt: {}", path.display());
+            }
+            for path in &verification.stale {
+                println!("stale: {}", path.display());
+            }
+            for path in &verification.foreign {
+                println!("foreign: {}", path.display());
+            }
+            println!("{verification}");
+
+            if repair || verification.is_healthy() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(error) => {
+            eprintln!("{error}");
+
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn dump(file: &Path) -> ExitCode {
+    let content = match fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(error) => {
+            eprintln!("failed to read ({}): {}", file.display(), error);
+
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let source = Source::new(get_source_kind(file), file.to_string_lossy(), content);
+
+    let tree = match ara_parser::parser::parse(&source) {
+        Ok(tree) => tree,
+        Err(report) => {
+            eprintln!("{report}");
+
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let dump = serde_json::json!({
+        "source": tree.source,
+        "definitions": tree.definitions,
+    });
+    match serde_json::to_string_pretty(&dump) {
+        Ok(json) => {
+            println!("{json}");
+
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{error}");
+
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn report(result: Result<usize, Error>, action: &str) -> ExitCode {
+    match result {
+        Ok(count) => {
+            println!("{action} {count} cache entries.");
+
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{error}");
+
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(feature = "remote")]
+use std::io::Read;
+#[cfg(feature = "remote")]
+use std::sync::atomic::AtomicBool;
+#[cfg(feature = "remote")]
+use std::sync::atomic::Ordering;
+#[cfg(feature = "remote")]
+use std::time::Duration;
+
+use crate::error::Error;
+
+pub trait RemoteCache: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error>;
+    fn put(&self, key: &str, data: &[u8]) -> Result<(), Error>;
+
+    /// Called before every parse, caches sharing state between runs can reset it here.
+    fn reset(&self) {}
+}
+
+#[cfg(feature = "remote")]
+pub struct HttpRemoteCache {
+    url: String,
+    agent: ureq::Agent,
+    unreachable: AtomicBool,
+}
+
+#[cfg(feature = "remote")]
+impl HttpRemoteCache {
+    pub fn new<U: Into<String>>(url: U) -> Self {
+        Self::with_timeout(url, Duration::from_secs(5))
+    }
+
+    pub fn with_timeout<U: Into<String>>(url: U, timeout: Duration) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+            unreachable: AtomicBool::new(false),
+        }
+    }
+
+    fn get_entry_url(&self, key: &str) -> String {
+        format!("{}/{}", self.url, key)
+    }
+
+    // Once the remote failed to respond, stop talking to it until the next run resets it,
+    // so an unreachable server costs a single timeout instead of one per file.
+    fn is_unreachable(&self) -> bool {
+        self.unreachable.load(Ordering::Relaxed)
+    }
+
+    fn map_error(&self, error: ureq::Error, key: &str) -> Error {
+        if let ureq::Error::Transport(_) = error {
+            self.unreachable.store(true, Ordering::Relaxed);
+        }
+
+        Error::remote(error.to_string()).with_key(key)
+    }
+}
+
+#[cfg(feature = "remote")]
+impl RemoteCache for HttpRemoteCache {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        if self.is_unreachable() {
+            return Ok(None);
+        }
+
+        let response = match self.agent.get(&self.get_entry_url(key)).call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(404, _)) => return Ok(None),
+            Err(error) => return Err(self.map_error(error, key)),
+        };
+
+        let mut data = Vec::new();
+        response.into_reader().read_to_end(&mut data)?;
+
+        Ok(Some(data))
+    }
+
+    fn put(&self, key: &str, data: &[u8]) -> Result<(), Error> {
+        if self.is_unreachable() {
+            return Ok(());
+        }
+
+        self.agent
+            .put(&self.get_entry_url(key))
+            .set("Content-Type", "application/octet-stream")
+            .send_bytes(data)
+            .map_err(|error| self.map_error(error, key))?;
+
+        Ok(())
+    }
+
+    // the cache is shared by every parser cloned from the same configuration, so a server
+    // that was unreachable during a previous run is given another chance.
+    fn reset(&self) {
+        self.unreachable.store(false, Ordering::Relaxed);
+    }
+}
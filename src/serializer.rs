@@ -1,12 +1,34 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use ara_parser::tree::Tree;
 use bincode::config;
 use bincode::config::Configuration;
 
 use crate::error::Error;
+use crate::symbol::Symbol;
+use crate::tree::FileStamp;
 use crate::tree::SignedTree;
 
 pub trait Serializer: Send + Sync {
+    fn name(&self) -> &str;
     fn serialize(&self, signed_tree: &SignedTree) -> Result<Vec<u8>, Error>;
     fn deserialize(&self, data: &[u8]) -> Result<SignedTree, Error>;
+
+    /// Reads the signature and stamp of an entry without decoding its tree.
+    ///
+    /// Returns `None` when the format doesn't support it, in which case the entry is
+    /// fully deserialized.
+    fn header(&self, _data: &[u8]) -> Option<EntryHeader> {
+        None
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EntryHeader {
+    pub signature: u64,
+    pub fingerprint: u64,
+    pub stamp: FileStamp,
 }
 
 pub struct BincodeSerializer {
@@ -28,6 +50,10 @@ impl Default for BincodeSerializer {
 }
 
 impl Serializer for BincodeSerializer {
+    fn name(&self) -> &str {
+        "bincode"
+    }
+
     fn serialize(&self, tree: &SignedTree) -> Result<Vec<u8>, Error> {
         Ok(bincode::encode_to_vec(tree, self.config)?)
     }
@@ -38,3 +64,295 @@ impl Serializer for BincodeSerializer {
         Ok(signed_tree)
     }
 }
+
+/// Writes entries as pretty printed JSON, so they can be inspected, diffed, and read by
+/// tools outside of Rust.
+///
+/// JSON entries are several times larger and slower to load than bincode entries.
+pub struct JsonSerializer;
+
+impl JsonSerializer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for JsonSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer for JsonSerializer {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn serialize(&self, signed_tree: &SignedTree) -> Result<Vec<u8>, Error> {
+        serde_json::to_vec_pretty(signed_tree).map_err(|error| Error::serialize(error.to_string()))
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<SignedTree, Error> {
+        serde_json::from_slice(data).map_err(|error| Error::deserialize(error.to_string()))
+    }
+}
+
+const COMPRESSED_ENTRY_MAGIC: &[u8] = b"\xFFARZ";
+const COMPRESSED_ENTRY_ZSTD: u8 = 1;
+const COMPRESSED_ENTRY_LZ4: u8 = 2;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    #[cfg(feature = "zstd")]
+    Zstd(i32),
+    Lz4,
+}
+
+pub struct CompressedSerializer {
+    inner: Arc<dyn Serializer>,
+    compression: Compression,
+}
+
+impl CompressedSerializer {
+    pub fn new(inner: Arc<dyn Serializer>, compression: Compression) -> Self {
+        Self { inner, compression }
+    }
+
+    fn compress(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let (flag, compressed) = match self.compression {
+            Compression::None => return Ok(data),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd(level) => (
+                COMPRESSED_ENTRY_ZSTD,
+                zstd::encode_all(data.as_slice(), level)
+                    .map_err(|error| Error::serialize(error.to_string()))?,
+            ),
+            Compression::Lz4 => (COMPRESSED_ENTRY_LZ4, lz4_flex::compress_prepend_size(&data)),
+        };
+
+        let mut entry = Vec::with_capacity(COMPRESSED_ENTRY_MAGIC.len() + 1 + compressed.len());
+        entry.extend_from_slice(COMPRESSED_ENTRY_MAGIC);
+        entry.push(flag);
+        entry.extend(compressed);
+
+        Ok(entry)
+    }
+
+    // entries are decompressed based on their own header rather than the configured
+    // compression, so entries written with different settings can coexist in the cache.
+    fn decompress<'a>(&self, data: &'a [u8]) -> Result<Cow<'a, [u8]>, Error> {
+        let Some(entry) = data.strip_prefix(COMPRESSED_ENTRY_MAGIC) else {
+            return Ok(Cow::Borrowed(data));
+        };
+
+        let decompressed = match entry.split_first() {
+            #[cfg(feature = "zstd")]
+            Some((&COMPRESSED_ENTRY_ZSTD, compressed)) => zstd::decode_all(compressed)
+                .map_err(|error| Error::deserialize(error.to_string()))?,
+            #[cfg(not(feature = "zstd"))]
+            Some((&COMPRESSED_ENTRY_ZSTD, _)) => {
+                return Err(Error::deserialize(
+                    "zstd compressed cache entry, but the `zstd` feature is disabled",
+                ))
+            }
+            Some((&COMPRESSED_ENTRY_LZ4, compressed)) => {
+                lz4_flex::decompress_size_prepended(compressed)
+                    .map_err(|error| Error::deserialize(error.to_string()))?
+            }
+            _ => {
+                return Err(Error::deserialize(
+                    "unknown compression flag in cache entry header",
+                ))
+            }
+        };
+
+        Ok(Cow::Owned(decompressed))
+    }
+}
+
+impl Serializer for CompressedSerializer {
+    // compression is recorded in each entry header, so it doesn't change the format name.
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn serialize(&self, tree: &SignedTree) -> Result<Vec<u8>, Error> {
+        self.compress(self.inner.serialize(tree)?)
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<SignedTree, Error> {
+        self.inner.deserialize(&self.decompress(data)?)
+    }
+
+    // peeking into a compressed entry would require decompressing it anyway.
+    fn header(&self, data: &[u8]) -> Option<EntryHeader> {
+        if data.starts_with(COMPRESSED_ENTRY_MAGIC) {
+            return None;
+        }
+
+        self.inner.header(data)
+    }
+}
+
+const ARCHIVED_ENTRY_MAGIC: &[u8] = b"\xFFARA";
+const ARCHIVED_ENTRY_HEADER_SIZE: usize = ARCHIVED_ENTRY_MAGIC.len() + 6 * 8;
+
+/// Lays entries out as a fixed size header, followed by the raw source content, the
+/// encoded symbols and the encoded tree, so the signature, stamp and content can be read
+/// in place.
+///
+/// The tree itself is still decoded with bincode, `ara_parser` trees can't be
+/// accessed without being decoded, so only warming the cache benefits from the layout:
+/// up to date entries are checked against their header, and never decoded.
+pub struct ArchivedSerializer {
+    config: Configuration,
+}
+
+/// A borrowed view over an archived cache entry, the symbols and the tree are only
+/// decoded on demand.
+#[derive(Debug, Copy, Clone)]
+pub struct ArchivedTree<'a> {
+    header: EntryHeader,
+    content: &'a str,
+    symbols: &'a [u8],
+    tree: &'a [u8],
+}
+
+impl ArchivedSerializer {
+    pub fn new() -> Self {
+        Self {
+            config: config::standard(),
+        }
+    }
+}
+
+impl Default for ArchivedSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer for ArchivedSerializer {
+    fn name(&self) -> &str {
+        "archived"
+    }
+
+    fn serialize(&self, signed_tree: &SignedTree) -> Result<Vec<u8>, Error> {
+        let symbols = bincode::encode_to_vec(&signed_tree.symbols, self.config)?;
+        let tree = bincode::encode_to_vec(&signed_tree.tree, self.config)?;
+
+        let mut entry = Vec::with_capacity(
+            ARCHIVED_ENTRY_HEADER_SIZE + signed_tree.content.len() + symbols.len() + tree.len(),
+        );
+        entry.extend_from_slice(ARCHIVED_ENTRY_MAGIC);
+        entry.extend_from_slice(&signed_tree.signature.to_le_bytes());
+        entry.extend_from_slice(&signed_tree.fingerprint.to_le_bytes());
+        entry.extend_from_slice(&signed_tree.size.to_le_bytes());
+        entry.extend_from_slice(&signed_tree.modified.to_le_bytes());
+        entry.extend_from_slice(&(signed_tree.content.len() as u64).to_le_bytes());
+        entry.extend_from_slice(&(symbols.len() as u64).to_le_bytes());
+        entry.extend_from_slice(signed_tree.content.as_bytes());
+        entry.extend(symbols);
+        entry.extend(tree);
+
+        Ok(entry)
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<SignedTree, Error> {
+        let archived = ArchivedTree::from_bytes(data)?;
+
+        Ok(SignedTree {
+            signature: archived.header.signature,
+            fingerprint: archived.header.fingerprint,
+            size: archived.header.stamp.size,
+            modified: archived.header.stamp.modified,
+            content: archived.content.to_string(),
+            symbols: archived.symbols()?,
+            tree: archived.tree()?,
+        })
+    }
+
+    fn header(&self, data: &[u8]) -> Option<EntryHeader> {
+        ArchivedTree::read_header(data)
+            .ok()
+            .map(|(header, _, _)| header)
+    }
+}
+
+impl<'a> ArchivedTree<'a> {
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, Error> {
+        let (header, content_length, symbols_length) = Self::read_header(data)?;
+
+        let rest = &data[ARCHIVED_ENTRY_HEADER_SIZE..];
+        if (rest.len() as u64) < content_length.saturating_add(symbols_length) {
+            return Err(Error::deserialize("archived cache entry is truncated"));
+        }
+
+        let (content, rest) = rest.split_at(content_length as usize);
+        let (symbols, tree) = rest.split_at(symbols_length as usize);
+        let content =
+            std::str::from_utf8(content).map_err(|error| Error::deserialize(error.to_string()))?;
+
+        Ok(Self {
+            header,
+            content,
+            symbols,
+            tree,
+        })
+    }
+
+    pub fn signature(&self) -> u64 {
+        self.header.signature
+    }
+
+    pub fn fingerprint(&self) -> u64 {
+        self.header.fingerprint
+    }
+
+    pub fn stamp(&self) -> FileStamp {
+        self.header.stamp
+    }
+
+    pub fn content(&self) -> &'a str {
+        self.content
+    }
+
+    pub fn symbols(&self) -> Result<Vec<Symbol>, Error> {
+        let (symbols, _): (Vec<Symbol>, _) =
+            bincode::decode_from_slice(self.symbols, config::standard())?;
+
+        Ok(symbols)
+    }
+
+    pub fn tree(&self) -> Result<Tree, Error> {
+        let (tree, _): (Tree, _) = bincode::decode_from_slice(self.tree, config::standard())?;
+
+        Ok(tree)
+    }
+
+    fn read_header(data: &[u8]) -> Result<(EntryHeader, u64, u64), Error> {
+        let fields = data
+            .get(..ARCHIVED_ENTRY_HEADER_SIZE)
+            .and_then(|header| header.strip_prefix(ARCHIVED_ENTRY_MAGIC))
+            .ok_or_else(|| Error::deserialize("invalid archived cache entry header"))?;
+
+        let field = |index: usize| {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&fields[index * 8..(index + 1) * 8]);
+
+            u64::from_le_bytes(bytes)
+        };
+
+        let header = EntryHeader {
+            signature: field(0),
+            fingerprint: field(1),
+            stamp: FileStamp {
+                size: field(2),
+                modified: field(3),
+            },
+        };
+
+        Ok((header, field(4), field(5)))
+    }
+}
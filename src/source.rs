@@ -1,4 +1,7 @@
+use std::path::Path;
 use std::path::PathBuf;
+
+use glob::Pattern;
 use walkdir::WalkDir;
 
 use crate::config::Config;
@@ -14,7 +17,18 @@ impl<'a> SourceFilesCollector<'a> {
         Self { config }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "collect",
+            skip_all,
+            fields(root = %self.config.root.display(), files)
+        )
+    )]
     pub fn collect(&self) -> Result<Vec<PathBuf>, Error> {
+        let include = self.compile_patterns(&self.config.include)?;
+        let exclude = self.compile_patterns(&self.config.exclude)?;
+
         let mut paths = vec![&self.config.source];
         paths.extend(&self.config.definitions);
 
@@ -22,21 +36,43 @@ impl<'a> SourceFilesCollector<'a> {
         for path in paths {
             let path = &self.config.root.join(path);
             if !path.is_dir() {
-                return Err(Error::InvalidPath(format!(
-                    "{} must be a directory and be relative to the project root directory.",
-                    path.display(),
-                )));
+                return Err(Error::invalid_path(
+                    "must be a directory and be relative to the project root directory.",
+                )
+                .with_path(path));
             }
             for entry in WalkDir::new(path) {
                 let entry = entry?;
                 if entry.file_type().is_file()
                     && entry.path().extension() == Some(ARA_SOURCE_EXTENSION.as_ref())
+                    && self.is_selected(entry.path(), &include, &exclude)
                 {
                     files.push(entry.into_path());
                 }
             }
         }
 
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("files", files.len());
+
         Ok(files)
     }
+
+    fn is_selected(&self, path: &Path, include: &[Pattern], exclude: &[Pattern]) -> bool {
+        let path = path.strip_prefix(&self.config.root).unwrap_or(path);
+
+        (include.is_empty() || include.iter().any(|pattern| pattern.matches_path(path)))
+            && !exclude.iter().any(|pattern| pattern.matches_path(path))
+    }
+
+    fn compile_patterns(&self, patterns: &[String]) -> Result<Vec<Pattern>, Error> {
+        patterns
+            .iter()
+            .map(|pattern| {
+                Pattern::new(pattern).map_err(|error| {
+                    Error::config(format!("invalid glob pattern `{pattern}`: {error}"))
+                })
+            })
+            .collect()
+    }
 }
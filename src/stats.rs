@@ -0,0 +1,95 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::time::Duration;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub files: usize,
+    pub cache_hits: usize,
+    pub remote_cache_hits: usize,
+    pub cache_misses_content_changed: usize,
+    /// Entries produced with another parser version, hasher, serializer or source kind.
+    pub cache_misses_config_changed: usize,
+    pub cache_misses_missing_entry: usize,
+    pub deserialize_failures: usize,
+    /// Bytes of cache entries read from the local and remote caches.
+    pub bytes_read: u64,
+    /// Bytes of cache entries written to the local and remote caches.
+    pub bytes_written: u64,
+    pub evicted_entries: usize,
+    pub evicted_bytes: u64,
+    /// Wall time spent collecting source files.
+    pub collect_duration: Duration,
+    /// Wall time spent building trees across all threads.
+    pub build_duration: Duration,
+    /// Time spent parsing sources, summed over all threads.
+    pub parse_duration: Duration,
+    /// Time spent reading and deserializing cache entries, summed over all threads.
+    pub cache_read_duration: Duration,
+    /// Time spent serializing and writing cache entries, summed over all threads.
+    pub cache_write_duration: Duration,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cache_misses(&self) -> usize {
+        self.cache_misses_content_changed
+            + self.cache_misses_config_changed
+            + self.cache_misses_missing_entry
+    }
+
+    pub fn merge(&mut self, other: &Stats) {
+        self.files += other.files;
+        self.cache_hits += other.cache_hits;
+        self.remote_cache_hits += other.remote_cache_hits;
+        self.cache_misses_content_changed += other.cache_misses_content_changed;
+        self.cache_misses_config_changed += other.cache_misses_config_changed;
+        self.cache_misses_missing_entry += other.cache_misses_missing_entry;
+        self.deserialize_failures += other.deserialize_failures;
+        self.bytes_read += other.bytes_read;
+        self.bytes_written += other.bytes_written;
+        self.evicted_entries += other.evicted_entries;
+        self.evicted_bytes += other.evicted_bytes;
+        self.collect_duration += other.collect_duration;
+        self.build_duration += other.build_duration;
+        self.parse_duration += other.parse_duration;
+        self.cache_read_duration += other.cache_read_duration;
+        self.cache_write_duration += other.cache_write_duration;
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "files: {}", self.files)?;
+        writeln!(f, "cache hits: {}", self.cache_hits)?;
+        writeln!(f, "remote cache hits: {}", self.remote_cache_hits)?;
+        writeln!(
+            f,
+            "cache misses (content changed): {}",
+            self.cache_misses_content_changed
+        )?;
+        writeln!(
+            f,
+            "cache misses (config changed): {}",
+            self.cache_misses_config_changed
+        )?;
+        writeln!(
+            f,
+            "cache misses (missing entry): {}",
+            self.cache_misses_missing_entry
+        )?;
+        writeln!(f, "deserialize failures: {}", self.deserialize_failures)?;
+        writeln!(f, "bytes read: {}", self.bytes_read)?;
+        writeln!(f, "bytes written: {}", self.bytes_written)?;
+        writeln!(f, "evicted entries: {}", self.evicted_entries)?;
+        writeln!(f, "evicted bytes: {}", self.evicted_bytes)?;
+        writeln!(f, "collect time: {:?}", self.collect_duration)?;
+        writeln!(f, "build time: {:?}", self.build_duration)?;
+        writeln!(f, "parse time: {:?}", self.parse_duration)?;
+        writeln!(f, "cache read time: {:?}", self.cache_read_duration)?;
+        write!(f, "cache write time: {:?}", self.cache_write_duration)
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use ara_parser::tree::definition::r#enum::EnumDefinition;
+use ara_parser::tree::definition::Definition;
+use ara_parser::tree::Node;
+use ara_parser::tree::Tree;
+use ara_source::source::SourceKind;
+use bincode::Decode;
+use bincode::Encode;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Class,
+    Interface,
+    Enum,
+    Function,
+    Constant,
+    TypeAlias,
+}
+
+impl SymbolKind {
+    pub fn namespace(&self) -> SymbolNamespace {
+        match self {
+            SymbolKind::Class
+            | SymbolKind::Interface
+            | SymbolKind::Enum
+            | SymbolKind::TypeAlias => SymbolNamespace::ClassLike,
+            SymbolKind::Function => SymbolNamespace::Function,
+            SymbolKind::Constant => SymbolNamespace::Constant,
+        }
+    }
+}
+
+/// A symbol declared at the top level of a tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Encode, Decode, Serialize, Deserialize)]
+pub struct Symbol {
+    /// The fully qualified name, without a leading backslash.
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The byte offset the declaration starts at.
+    pub from: usize,
+    /// The byte offset the declaration ends at.
+    pub to: usize,
+}
+
+/// The namespaces symbols are declared in, a function and a class can share a name.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SymbolNamespace {
+    /// Classes, interfaces, enums and type aliases.
+    ClassLike,
+    Function,
+    Constant,
+}
+
+/// Where a symbol of the index is declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolLocation {
+    pub origin: String,
+    pub source_kind: SourceKind,
+    pub kind: SymbolKind,
+    pub from: usize,
+    pub to: usize,
+}
+
+/// Maps the fully qualified name of every symbol of a forest to its declarations, names
+/// are indexed per namespace.
+#[derive(Debug, Default, Clone)]
+pub struct SymbolIndex {
+    symbols: HashMap<(SymbolNamespace, String), Vec<SymbolLocation>>,
+}
+
+impl SymbolIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the symbols declared in a source, every declaration of a name is kept.
+    pub fn insert(&mut self, origin: &str, source_kind: SourceKind, symbols: &[Symbol]) {
+        for symbol in symbols {
+            self.symbols
+                .entry((symbol.kind.namespace(), symbol.name.clone()))
+                .or_default()
+                .push(SymbolLocation {
+                    origin: origin.to_string(),
+                    source_kind,
+                    kind: symbol.kind,
+                    from: symbol.from,
+                    to: symbol.to,
+                });
+        }
+    }
+
+    /// Looks up the first declaration of a symbol by its fully qualified name, names are
+    /// case sensitive.
+    pub fn get(&self, namespace: SymbolNamespace, name: &str) -> Option<&SymbolLocation> {
+        self.get_all(namespace, name).first()
+    }
+
+    /// Looks up every declaration of a symbol by its fully qualified name, in parse order.
+    pub fn get_all(&self, namespace: SymbolNamespace, name: &str) -> &[SymbolLocation] {
+        let name = name.strip_prefix('\\').unwrap_or(name);
+
+        self.symbols
+            .get(&(namespace, name.to_string()))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The number of indexed names.
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (SymbolNamespace, &str, &[SymbolLocation])> {
+        self.symbols
+            .iter()
+            .map(|((namespace, name), locations)| (*namespace, name.as_str(), locations.as_slice()))
+    }
+}
+
+/// Collects the classes, interfaces, enums, functions, constants and type aliases
+/// declared in a tree, in declaration order.
+pub fn get_symbols(tree: &Tree) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    collect_symbols(&tree.definitions.definitions, "", &mut symbols);
+
+    symbols
+}
+
+fn collect_symbols(definitions: &[Definition], namespace: &str, symbols: &mut Vec<Symbol>) {
+    for definition in definitions {
+        let (name, kind) = match definition {
+            Definition::Namespace(namespace) => {
+                let name = namespace.name.value.to_string();
+                collect_symbols(
+                    &namespace.definitions,
+                    name.trim_start_matches('\\'),
+                    symbols,
+                );
+
+                continue;
+            }
+            Definition::Use(_) => continue,
+            Definition::TypeAlias(alias) => (&alias.name.name, SymbolKind::TypeAlias),
+            Definition::Constant(constant) => (&constant.name, SymbolKind::Constant),
+            Definition::Function(function) => (&function.name, SymbolKind::Function),
+            Definition::Interface(interface) => (&interface.name, SymbolKind::Interface),
+            Definition::Enum(r#enum) => match r#enum.as_ref() {
+                EnumDefinition::Backed(r#enum) => (&r#enum.name, SymbolKind::Enum),
+                EnumDefinition::Unit(r#enum) => (&r#enum.name, SymbolKind::Enum),
+            },
+            Definition::Class(class) => (&class.name, SymbolKind::Class),
+        };
+
+        let name = name.value.to_string();
+        symbols.push(Symbol {
+            name: match namespace {
+                "" => name,
+                namespace => format!("{namespace}\\{name}"),
+            },
+            kind,
+            from: definition.initial_position(),
+            to: definition.final_position(),
+        });
+    }
+}
@@ -1,146 +1,835 @@
 use bincode::Decode;
 use bincode::Encode;
+use serde::Deserialize;
+use serde::Serialize;
 use std::fs;
 use std::fs::File;
+use std::fs::Metadata;
+use std::io::ErrorKind;
 use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
+use ara_parser::tree::definition::DefinitionTree;
 use ara_parser::tree::Tree;
 use ara_source::source::Source;
 use ara_source::source::SourceKind;
 
+use crate::config::CacheKey;
 use crate::config::Config;
 use crate::error::Error;
+use crate::serializer::CompressedSerializer;
+use crate::serializer::Serializer;
+use crate::stats::Stats;
+use crate::symbol::get_symbols;
+use crate::symbol::Symbol;
 use crate::ARA_CACHED_SOURCE_EXTENSION;
 use crate::ARA_DEFINITION_EXTENSION;
+use crate::ARA_PARSER_VERSION;
 
-#[derive(Debug, Hash, Encode, Decode)]
+// bumped whenever the layout of cache entries changes, so older entries are ignored.
+const CACHE_FORMAT_VERSION: &str = "2";
+
+#[derive(Debug, Hash, Encode, Decode, Serialize, Deserialize)]
 pub struct SignedTree {
     pub signature: u64,
+    /// Identifies the settings the tree was produced with, see `get_fingerprint`.
+    pub fingerprint: u64,
+    pub size: u64,
+    pub modified: u64,
+    pub content: String,
+    /// The symbols declared in the tree, so cached trees don't have to be walked again.
+    pub symbols: Vec<Symbol>,
+    #[serde(with = "TreeDefinition")]
     pub tree: Tree,
 }
 
+// `ara_parser` only implements serde for the definitions, not for the tree wrapping them.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Tree")]
+struct TreeDefinition {
+    source: String,
+    definitions: DefinitionTree,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FileStamp {
+    pub size: u64,
+    pub modified: u64,
+}
+
+// a source, along with its tree and the symbols it declares.
+type SourceTree = (Source, Tree, Vec<Symbol>);
+
+// an entry read by the modification time fast path, handed over to the regular cache lookup
+// when the stamp doesn't match, so it isn't read and decoded twice.
+enum CachedEntry {
+    Read(Vec<u8>),
+    Decoded(Box<SignedTree>),
+}
+
 pub struct TreeBuilder<'a> {
     config: &'a Config,
+    serializer: CompressedSerializer,
+    stats: Stats,
 }
 
 impl<'a> TreeBuilder<'a> {
     pub fn new(config: &'a Config) -> Self {
-        Self { config }
+        Self {
+            config,
+            serializer: config.entry_serializer(),
+            stats: Stats::new(),
+        }
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
     }
 
-    pub fn build(&self, source_path: &Path) -> Result<(Source, Tree), Error> {
+    /// Builds the tree of a source, along with the symbols it declares.
+    pub fn build(&mut self, source_path: &Path) -> Result<(Source, Tree, Vec<Symbol>), Error> {
+        // trees are only left undecoded when warming.
+        self.build_from_path(source_path, true)
+            .map(|source_tree| source_tree.unwrap())
+    }
+
+    /// Makes sure the cache holds an up to date entry for a source.
+    ///
+    /// Entries trusted through their recorded stamp aren't decoded when the serializer can
+    /// read their header in place, see `ArchivedSerializer`.
+    pub fn warm(&mut self, source_path: &Path) -> Result<(), Error> {
+        self.build_from_path(source_path, false).map(|_| ())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "build",
+            skip_all,
+            fields(origin = %self.strip_root(source_path), size, outcome, duration_us)
+        )
+    )]
+    fn build_from_path(
+        &mut self,
+        source_path: &Path,
+        decode: bool,
+    ) -> Result<Option<SourceTree>, Error> {
+        let start = Instant::now();
+        let result = self.build_from_stamp(source_path, decode);
+        record("duration_us", start.elapsed().as_micros() as u64);
+
+        result
+    }
+
+    fn build_from_stamp(
+        &mut self,
+        source_path: &Path,
+        decode: bool,
+    ) -> Result<Option<SourceTree>, Error> {
+        self.stats.files += 1;
+
+        let metadata =
+            fs::metadata(source_path).map_err(|error| Error::from(error).with_path(source_path))?;
+        let stamp = FileStamp::from_metadata(&metadata);
+        record("size", stamp.size);
+        let entry = match self.get_from_cache_by_stamp(source_path, &stamp, decode) {
+            Ok(source_tree) => {
+                self.stats.cache_hits += 1;
+                record("outcome", "hit");
+
+                return Ok(source_tree);
+            }
+            Err(entry) => entry,
+        };
+
         let source = self.build_source(source_path)?;
-        let tree = self.build_tree(&source)?;
+        let (tree, symbols) = self.build_tree(&source, &stamp, entry)?;
 
-        Ok((source, tree))
+        Ok(Some((source, tree, symbols)))
     }
 
-    fn build_tree(&self, source: &Source) -> Result<Tree, Error> {
-        if self.config.cache.is_none() {
-            return ara_parser::parser::parse(source).map_err(Error::ParseError);
+    fn build_tree(
+        &mut self,
+        source: &Source,
+        stamp: &FileStamp,
+        entry: Option<CachedEntry>,
+    ) -> Result<(Tree, Vec<Symbol>), Error> {
+        if self.config.cache.is_none() && self.config.remote.is_none() {
+            record("outcome", "uncached");
+
+            let tree = self.parse(source)?;
+            let symbols = get_symbols(&tree);
+
+            return Ok((tree, symbols));
         }
 
-        let cached_file_path = self.get_cached_file_path(source);
-        let tree = self.get_from_cache(source, &cached_file_path).or_else(
-            |error| -> Result<Tree, Error> {
-                if let Error::DeserializeError(_) = error {
-                    log::error!(
-                        "error while deserializing cached file ({}) for source ({}): {}",
-                        self.strip_root(&cached_file_path),
-                        source.origin.as_ref().unwrap(),
-                        error
-                    );
+        let signature = self.config.hasher.hash(&source.content);
+        let mut miss = None;
+        if self.config.cache.is_some() {
+            match self.get_from_cache(source, signature, entry) {
+                Ok(signed_tree) => {
+                    self.stats.cache_hits += 1;
+                    record("outcome", "hit");
+                    // content addressed entries are shared between sources with different
+                    // stamps, and the stamp isn't trusted with strict hashing anyway.
+                    if signed_tree.stamp() == *stamp || !self.uses_stamps() {
+                        return Ok((signed_tree.tree, signed_tree.symbols));
+                    }
+
+                    // the source was touched without being changed, refresh the entry so the
+                    // next run can take the fast path again.
+                    return self.save_to_local_cache(source, signature, stamp, signed_tree.tree);
                 }
+                Err(error) => miss = Some(error),
+            }
+        }
 
-                let tree = ara_parser::parser::parse(source).map_err(Error::ParseError)?;
-                self.save_to_cache(source, tree, &cached_file_path)
-            },
-        )?;
+        if self.config.remote.is_some() {
+            match self.get_from_remote_cache(source, signature, stamp) {
+                Ok(tree_symbols) => {
+                    self.stats.remote_cache_hits += 1;
+                    record("outcome", "remote_hit");
+
+                    return Ok(tree_symbols);
+                }
+                Err(error) => {
+                    miss.get_or_insert(error);
+                }
+            }
+        }
+
+        match miss {
+            Some(Error::CacheMiss) => {
+                self.stats.cache_misses_content_changed += 1;
+                record("outcome", "miss_content_changed");
+            }
+            Some(Error::FingerprintMismatch) => {
+                self.stats.cache_misses_config_changed += 1;
+                record("outcome", "miss_config_changed");
+            }
+            Some(Error::DeserializeError { .. }) => {
+                self.stats.deserialize_failures += 1;
+                record("outcome", "deserialize_failure");
+            }
+            _ => {
+                self.stats.cache_misses_missing_entry += 1;
+                record("outcome", "miss_missing_entry");
+            }
+        }
+
+        let tree = self.parse(source)?;
+
+        self.save_to_cache(source, signature, stamp, tree)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn parse(&mut self, source: &Source) -> Result<Tree, Error> {
+        let start = Instant::now();
+        let tree = ara_parser::parser::parse(source).map_err(Error::ParseError);
+        self.stats.parse_duration += start.elapsed();
 
-        Ok(tree)
+        tree
     }
 
-    fn get_from_cache(&self, source: &Source, cached_file_path: &PathBuf) -> Result<Tree, Error> {
-        let signed_tree = self
-            .config
-            .serializer
-            .deserialize(&fs::read(cached_file_path)?)?;
+    // trusts the size and modification time recorded in the cache entry, so unchanged
+    // sources are neither read nor hashed.
+    //
+    // when the entry can't be trusted, the entry that was read, if any, is returned instead.
+    // when the entry isn't decoded, no tree is returned.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "cache_read", skip_all)
+    )]
+    fn get_from_cache_by_stamp(
+        &mut self,
+        source_path: &Path,
+        stamp: &FileStamp,
+        decode: bool,
+    ) -> Result<Option<SourceTree>, Option<CachedEntry>> {
+        if !self.uses_stamps() || !stamp.is_known() || self.config.cache.is_none() {
+            return Err(None);
+        }
+
+        let start = Instant::now();
+        let origin = self.strip_root(source_path);
+        let cached_file_path = self.get_origin_cached_file_path(&origin);
+        let data = fs::read(&cached_file_path).map_err(|_| None)?;
+        self.stats.bytes_read += data.len() as u64;
+        let kind = get_source_kind(source_path);
+        let fingerprint = get_fingerprint(self.config, kind);
+        let header = self.serializer.header(&data);
+        let signed_tree = match header {
+            Some(header) if header.stamp != *stamp || header.fingerprint != fingerprint => None,
+            Some(_) if !decode => {
+                self.stats.cache_read_duration += start.elapsed();
+
+                let cache = self.strip_root(&cached_file_path);
+                log::info!(
+                    event = "cache_hit", origin = origin.as_str(), cache = cache.as_str();
+                    "found up to date cache entry ({}) for ({}) without decoding it.",
+                    cache,
+                    origin,
+                );
+                self.mark_as_used(&cached_file_path);
+
+                return Ok(None);
+            }
+            _ => self.deserialize(&data).ok(),
+        };
+        self.stats.cache_read_duration += start.elapsed();
 
-        let current_signature = self.config.hasher.hash(&source.content);
-        if signed_tree.signature != current_signature {
+        let signed_tree = match signed_tree {
+            Some(signed_tree)
+                if signed_tree.stamp() == *stamp && signed_tree.fingerprint == fingerprint =>
+            {
+                signed_tree
+            }
+            Some(signed_tree) => return Err(Some(CachedEntry::Decoded(Box::new(signed_tree)))),
+            None => return Err(Some(CachedEntry::Read(data))),
+        };
+
+        let cache = self.strip_root(&cached_file_path);
+        log::info!(
+            event = "cache_hit", origin = origin.as_str(), cache = cache.as_str();
+            "loaded ({}) parsed source from cache ({}) without reading the source.",
+            origin,
+            cache,
+        );
+        self.mark_as_used(&cached_file_path);
+
+        let source = Source::new(kind, &origin, signed_tree.content);
+        let mut tree = signed_tree.tree;
+        tree.source = origin;
+
+        Ok(Some((source, tree, signed_tree.symbols)))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "cache_read", skip_all)
+    )]
+    fn get_from_cache(
+        &mut self,
+        source: &Source,
+        signature: u64,
+        entry: Option<CachedEntry>,
+    ) -> Result<SignedTree, Error> {
+        let start = Instant::now();
+        let cached_file_path = self.get_cached_file_path(source, signature);
+        let signed_tree = match entry {
+            Some(CachedEntry::Decoded(signed_tree)) => {
+                self.check_signed_tree(source, signature, *signed_tree)
+            }
+            Some(CachedEntry::Read(data)) => self.load_signed_tree(source, signature, &data),
+            None => fs::read(&cached_file_path)
+                .map_err(Error::from)
+                .and_then(|data| {
+                    self.stats.bytes_read += data.len() as u64;
+
+                    self.load_signed_tree(source, signature, &data)
+                }),
+        }
+        .map_err(|error| error.with_path(&cached_file_path));
+        self.stats.cache_read_duration += start.elapsed();
+
+        match &signed_tree {
+            Ok(_) => {
+                let origin = source.origin.as_deref().unwrap();
+                let cache = self.strip_root(&cached_file_path);
+                log::info!(
+                    event = "cache_hit", origin, cache = cache.as_str();
+                    "loaded ({}) parsed source from cache ({}).",
+                    origin,
+                    cache,
+                );
+                self.mark_as_used(&cached_file_path);
+            }
+            Err(error @ Error::DeserializeError { .. }) => {
+                let origin = source.origin.as_deref().unwrap();
+                let cache = self.strip_root(&cached_file_path);
+                log::error!(
+                    event = "cache_deserialize_error", origin, cache = cache.as_str();
+                    "error while deserializing cached file ({}) for source ({}): {}",
+                    cache,
+                    origin,
+                    error
+                )
+            }
+            Err(_) => {}
+        }
+
+        signed_tree
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "remote_cache_read", skip_all)
+    )]
+    fn get_from_remote_cache(
+        &mut self,
+        source: &Source,
+        signature: u64,
+        stamp: &FileStamp,
+    ) -> Result<(Tree, Vec<Symbol>), Error> {
+        let remote = self.config.remote.as_ref().unwrap();
+
+        let start = Instant::now();
+        let key = self.get_content_key(source, signature);
+        let data = match remote.get(&key) {
+            Ok(Some(data)) => data,
+            Ok(None) => return Err(std::io::Error::from(ErrorKind::NotFound).into()),
+            Err(error) => {
+                let origin = source.origin.as_deref().unwrap();
+                log::warn!(
+                    event = "remote_cache_read_error", origin, cache = key.as_str();
+                    "failed to fetch ({}) from remote cache, falling back to parsing: {}",
+                    origin,
+                    error
+                );
+
+                return Err(error);
+            }
+        };
+        self.stats.bytes_read += data.len() as u64;
+        let signed_tree = self.load_signed_tree(source, signature, &data);
+        self.stats.cache_read_duration += start.elapsed();
+
+        match signed_tree {
+            Ok(signed_tree) => {
+                let origin = source.origin.as_deref().unwrap();
+                log::info!(
+                    event = "remote_cache_hit", origin, cache = key.as_str();
+                    "loaded ({}) parsed source from remote cache ({}).",
+                    origin,
+                    key,
+                );
+
+                // the entry carries the stamp of the machine that uploaded it.
+                self.save_to_local_cache(source, signature, stamp, signed_tree.tree)
+            }
+            Err(error) => {
+                let origin = source.origin.as_deref().unwrap();
+                log::error!(
+                    event = "remote_cache_deserialize_error", origin, cache = key.as_str();
+                    "error while loading remote cache entry ({}) for source ({}): {}",
+                    key,
+                    origin,
+                    error
+                );
+
+                Err(error)
+            }
+        }
+    }
+
+    fn load_signed_tree(
+        &self,
+        source: &Source,
+        signature: u64,
+        data: &[u8],
+    ) -> Result<SignedTree, Error> {
+        // formats with a readable header detect changed entries without decoding the tree.
+        if let Some(header) = self.serializer.header(data) {
+            self.check_entry(source, signature, header.signature, header.fingerprint)?;
+        }
+
+        let signed_tree = self.deserialize(data)?;
+
+        self.check_signed_tree(source, signature, signed_tree)
+    }
+
+    fn check_signed_tree(
+        &self,
+        source: &Source,
+        signature: u64,
+        mut signed_tree: SignedTree,
+    ) -> Result<SignedTree, Error> {
+        self.check_entry(
+            source,
+            signature,
+            signed_tree.signature,
+            signed_tree.fingerprint,
+        )?;
+
+        // content addressed entries are shared between files, so the origin stored
+        // in the entry may belong to another file with the same content.
+        signed_tree.tree.source = source.origin.clone().unwrap();
+
+        Ok(signed_tree)
+    }
+
+    fn check_entry(
+        &self,
+        source: &Source,
+        signature: u64,
+        entry_signature: u64,
+        entry_fingerprint: u64,
+    ) -> Result<(), Error> {
+        if entry_signature != signature {
+            let origin = source.origin.as_deref().unwrap();
             log::warn!(
+                event = "cache_miss_source_changed", origin;
                 "cache miss due to source change ({}).",
-                source.origin.as_ref().unwrap(),
+                origin,
             );
 
             return Err(Error::CacheMiss);
         }
 
-        log::info!(
-            "loaded ({}) parsed source from cache ({}).",
-            source.origin.as_ref().unwrap(),
-            self.strip_root(cached_file_path),
-        );
+        if entry_fingerprint != get_fingerprint(self.config, source.kind) {
+            let origin = source.origin.as_deref().unwrap();
+            log::warn!(
+                event = "cache_miss_config_changed", origin;
+                "cache miss due to configuration change ({}).",
+                origin,
+            );
+
+            return Err(Error::FingerprintMismatch);
+        }
 
-        Ok(signed_tree.tree)
+        Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "cache_write", skip_all)
+    )]
     fn save_to_cache(
-        &self,
+        &mut self,
+        source: &Source,
+        signature: u64,
+        stamp: &FileStamp,
+        tree: Tree,
+    ) -> Result<(Tree, Vec<Symbol>), Error> {
+        if self.config.remote.is_none() {
+            return self.save_to_local_cache(source, signature, stamp, tree);
+        }
+
+        let start = Instant::now();
+        let signed_tree = SignedTree::new(
+            signature,
+            get_fingerprint(self.config, source.kind),
+            stamp,
+            source.content.clone(),
+            tree,
+        );
+        let serialized = self.serialize(&signed_tree)?;
+
+        self.write_to_cache(source, signature, &serialized)?;
+
+        if let Some(remote) = &self.config.remote {
+            let key = self.get_content_key(source, signature);
+            match remote.put(&key, &serialized) {
+                Ok(()) => {
+                    self.stats.bytes_written += serialized.len() as u64;
+
+                    log::info!(
+                        event = "remote_cache_write", origin = signed_tree.tree.source.as_str(), cache = key.as_str();
+                        "saved ({}) parsed source to remote cache ({}).",
+                        &signed_tree.tree.source,
+                        key,
+                    )
+                }
+                Err(error) => log::warn!(
+                    event = "remote_cache_write_error", origin = signed_tree.tree.source.as_str(), cache = key.as_str();
+                    "failed to save ({}) to remote cache: {}",
+                    &signed_tree.tree.source,
+                    error
+                ),
+            }
+        }
+        self.stats.cache_write_duration += start.elapsed();
+
+        Ok((signed_tree.tree, signed_tree.symbols))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "local_cache_write", skip_all)
+    )]
+    fn save_to_local_cache(
+        &mut self,
         source: &Source,
+        signature: u64,
+        stamp: &FileStamp,
         tree: Tree,
-        cached_file_path: &PathBuf,
-    ) -> Result<Tree, Error> {
-        let mut file = File::create(cached_file_path)?;
-        let signed_tree = SignedTree::new(self.config.hasher.hash(&source.content), tree);
+    ) -> Result<(Tree, Vec<Symbol>), Error> {
+        if !self.is_cache_writable() {
+            let symbols = get_symbols(&tree);
+
+            return Ok((tree, symbols));
+        }
 
-        let serialized = self.config.serializer.serialize(&signed_tree)?;
-        file.write_all(&serialized)?;
+        let start = Instant::now();
+        let signed_tree = SignedTree::new(
+            signature,
+            get_fingerprint(self.config, source.kind),
+            stamp,
+            source.content.clone(),
+            tree,
+        );
+        let serialized = self.serialize(&signed_tree)?;
+
+        self.write_to_cache(source, signature, &serialized)?;
+        self.stats.cache_write_duration += start.elapsed();
+
+        Ok((signed_tree.tree, signed_tree.symbols))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(origin = %signed_tree.tree.source))
+    )]
+    fn serialize(&self, signed_tree: &SignedTree) -> Result<Vec<u8>, Error> {
+        self.serializer.serialize(signed_tree)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(size = data.len()))
+    )]
+    fn deserialize(&self, data: &[u8]) -> Result<SignedTree, Error> {
+        self.serializer.deserialize(data)
+    }
+
+    fn write_to_cache(
+        &mut self,
+        source: &Source,
+        signature: u64,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        if !self.is_cache_writable() {
+            return Ok(());
+        }
 
+        // entries are written to a temporary file first, so other threads and processes never
+        // read a partially written entry.
+        let cached_file_path = self.get_cached_file_path(source, signature);
+        let temporary_file_path = get_temporary_file_path(&cached_file_path);
+        File::create(&temporary_file_path)
+            .and_then(|mut file| file.write_all(data))
+            .and_then(|_| fs::rename(&temporary_file_path, &cached_file_path))
+            .map_err(|error| {
+                let _ = fs::remove_file(&temporary_file_path);
+
+                Error::from(error).with_path(&cached_file_path)
+            })?;
+        self.stats.bytes_written += data.len() as u64;
+
+        let origin = source.origin.as_deref().unwrap();
+        let cache = self.strip_root(&cached_file_path);
         log::info!(
+            event = "cache_write", origin, cache = cache.as_str();
             "saved ({}) parsed source to cache ({}).",
-            &signed_tree.tree.source,
-            self.strip_root(cached_file_path),
+            origin,
+            cache,
         );
 
-        Ok(signed_tree.tree)
+        Ok(())
     }
 
-    fn get_cached_file_path(&self, source: &Source) -> PathBuf {
+    // the modification time of an entry is its last access time, which is what the
+    // least recently used eviction relies on.
+    fn mark_as_used(&self, cached_file_path: &Path) {
+        if !self.config.is_cache_limited() || !self.is_cache_writable() {
+            return;
+        }
+
+        let result = File::options()
+            .write(true)
+            .open(cached_file_path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+
+        if let Err(error) = result {
+            let cache = self.strip_root(cached_file_path);
+            log::warn!(
+                event = "cache_touch_error", cache = cache.as_str();
+                "failed to update access time of cache file ({}): {}",
+                cache,
+                error
+            );
+        }
+    }
+
+    // stamps are only recorded for origin keyed entries, which belong to a single source.
+    fn uses_stamps(&self) -> bool {
+        self.config.cache_key == CacheKey::Origin && !self.config.strict_hashing
+    }
+
+    fn is_cache_writable(&self) -> bool {
+        self.config.cache.is_some() && !self.config.read_only_cache
+    }
+
+    fn get_content_key(&self, source: &Source, signature: u64) -> String {
+        get_content_key(get_fingerprint(self.config, source.kind), signature)
+    }
+
+    fn get_cached_file_path(&self, source: &Source, signature: u64) -> PathBuf {
         let cache_path = self.config.cache.as_ref().unwrap();
-        cache_path
-            .join(
-                self.config
-                    .hasher
-                    .hash(source.origin.as_ref().unwrap())
-                    .to_string(),
-            )
-            .with_extension(ARA_CACHED_SOURCE_EXTENSION)
+
+        cache_path.join(get_cache_entry_name(
+            self.config,
+            source.origin.as_ref().unwrap(),
+            get_fingerprint(self.config, source.kind),
+            signature,
+        ))
+    }
+
+    fn get_origin_cached_file_path(&self, origin: &str) -> PathBuf {
+        let cache_path = self.config.cache.as_ref().unwrap();
+        let name = self.config.hasher.hash(origin);
+
+        cache_path.join(format!("{name}.{ARA_CACHED_SOURCE_EXTENSION}"))
     }
 
     fn build_source(&self, source_path: &Path) -> Result<Source, Error> {
         let origin = self.strip_root(source_path);
-        let kind = match source_path.extension() {
-            Some(extension) if extension == ARA_DEFINITION_EXTENSION => SourceKind::Definition,
-            _ => SourceKind::Script,
-        };
-        let content = fs::read_to_string(source_path)?;
+        let content = fs::read_to_string(source_path)
+            .map_err(|error| Error::from(error).with_path(source_path))?;
 
-        Ok(Source::new(kind, origin, content))
+        Ok(Source::new(get_source_kind(source_path), origin, content))
     }
 
-    fn strip_root(&self, path: &Path) -> String {
+    pub(crate) fn strip_root(&self, path: &Path) -> String {
         path.strip_prefix(&self.config.root)
-            .map(|path| path.to_string_lossy())
-            .unwrap()
+            .unwrap_or(path)
+            .to_string_lossy()
             .to_string()
     }
 }
 
+// records a field of the current `build` span.
+#[cfg(feature = "tracing")]
+fn record<V: tracing::Value>(field: &str, value: V) {
+    tracing::Span::current().record(field, value);
+}
+
+#[cfg(not(feature = "tracing"))]
+fn record<V>(_field: &str, _value: V) {}
+
+// unique per process and write, so concurrent writers of the same entry don't collide.
+fn get_temporary_file_path(cached_file_path: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let mut name = cached_file_path
+        .file_name()
+        .unwrap_or_default()
+        .to_os_string();
+    name.push(format!(
+        ".{}.{}.tmp",
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    cached_file_path.with_file_name(name)
+}
+
+pub(crate) fn get_content_key(fingerprint: u64, signature: u64) -> String {
+    format!("{ARA_PARSER_VERSION}-{fingerprint}-{signature}")
+}
+
+pub(crate) fn get_cache_entry_name(
+    config: &Config,
+    origin: &str,
+    fingerprint: u64,
+    signature: u64,
+) -> String {
+    let name = match config.cache_key {
+        CacheKey::Origin => config.hasher.hash(origin).to_string(),
+        CacheKey::Content => get_content_key(fingerprint, signature),
+    };
+
+    format!("{name}.{ARA_CACHED_SOURCE_EXTENSION}")
+}
+
+/// Identifies everything, besides the source content, that the cached tree of a source
+/// depends on: the parser version, the cache entry layout, the hasher, the serializer, and
+/// the kind of the source.
+pub(crate) fn get_fingerprint(config: &Config, kind: SourceKind) -> u64 {
+    let kind = match kind {
+        SourceKind::Definition => "definition",
+        SourceKind::Script => "script",
+    };
+
+    config.hasher.hash(&format!(
+        "{}:{}:{}:{}:{}",
+        ARA_PARSER_VERSION,
+        CACHE_FORMAT_VERSION,
+        config.hasher.name(),
+        config.serializer.name(),
+        kind
+    ))
+}
+
+/// Returns the kind of the source at the given path, files ending in `.d.ara` are definitions.
+// `Path::extension` only returns the last extension, `ara` for definitions.
+pub fn get_source_kind(source_path: &Path) -> SourceKind {
+    let is_definition = source_path
+        .file_name()
+        .map(|name| {
+            name.to_string_lossy()
+                .ends_with(&format!(".{ARA_DEFINITION_EXTENSION}"))
+        })
+        .unwrap_or(false);
+
+    if is_definition {
+        SourceKind::Definition
+    } else {
+        SourceKind::Script
+    }
+}
+
 impl SignedTree {
-    pub fn new(signature: u64, tree: Tree) -> Self {
-        Self { signature, tree }
+    pub fn new(
+        signature: u64,
+        fingerprint: u64,
+        stamp: &FileStamp,
+        content: String,
+        tree: Tree,
+    ) -> Self {
+        Self {
+            signature,
+            fingerprint,
+            size: stamp.size,
+            modified: stamp.modified,
+            content,
+            symbols: get_symbols(&tree),
+            tree,
+        }
+    }
+
+    pub fn stamp(&self) -> FileStamp {
+        FileStamp {
+            size: self.size,
+            modified: self.modified,
+        }
+    }
+}
+
+impl FileStamp {
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or(0);
+
+        Self {
+            size: metadata.len(),
+            modified,
+        }
+    }
+
+    // platforms without modification times report zero, which must never be trusted.
+    pub fn is_known(&self) -> bool {
+        self.modified != 0
     }
 }
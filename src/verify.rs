@@ -0,0 +1,179 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::config::CacheKey;
+use crate::config::Config;
+use crate::error::Error;
+use crate::serializer::CompressedSerializer;
+use crate::serializer::Serializer;
+use crate::tree::get_cache_entry_name;
+use crate::tree::get_fingerprint;
+use crate::tree::get_source_kind;
+use crate::ARA_CACHED_SOURCE_EXTENSION;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CacheEntryStatus {
+    Valid,
+    /// The entry can't be deserialized, or its signature doesn't match its content.
+    Corrupt,
+    /// The source the entry was built from has changed or no longer exists.
+    Stale,
+    /// The file wasn't written by this configuration.
+    Foreign,
+}
+
+#[derive(Debug, Default)]
+pub struct CacheVerification {
+    pub valid: Vec<PathBuf>,
+    pub corrupt: Vec<PathBuf>,
+    pub stale: Vec<PathBuf>,
+    pub foreign: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+}
+
+pub struct CacheVerifier<'a> {
+    config: &'a Config,
+    serializer: CompressedSerializer,
+}
+
+impl<'a> CacheVerifier<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self {
+            config,
+            serializer: config.entry_serializer(),
+        }
+    }
+
+    pub fn verify(&self) -> Result<CacheVerification, Error> {
+        self.walk(false)
+    }
+
+    /// Verifies the cache, and removes every corrupt, stale and foreign file.
+    pub fn repair(&self) -> Result<CacheVerification, Error> {
+        self.walk(true)
+    }
+
+    pub fn verify_entry(&self, path: &Path) -> CacheEntryStatus {
+        let is_entry = path.is_file()
+            && path
+                .file_name()
+                .map(|name| {
+                    name.to_string_lossy()
+                        .ends_with(ARA_CACHED_SOURCE_EXTENSION)
+                })
+                .unwrap_or(false);
+        if !is_entry {
+            return CacheEntryStatus::Foreign;
+        }
+
+        let signed_tree = match fs::read(path)
+            .map_err(Error::from)
+            .and_then(|data| self.serializer.deserialize(&data))
+        {
+            Ok(signed_tree) => signed_tree,
+            Err(error) => {
+                log::warn!(
+                    event = "cache_corrupt", cache:% = path.display();
+                    "corrupt cache entry ({}): {}",
+                    path.display(),
+                    error
+                );
+
+                return CacheEntryStatus::Corrupt;
+            }
+        };
+
+        if self.config.hasher.hash(&signed_tree.content) != signed_tree.signature {
+            log::warn!(
+                event = "cache_corrupt", cache:% = path.display();
+                "corrupt cache entry ({}): signature mismatch.",
+                path.display()
+            );
+
+            return CacheEntryStatus::Corrupt;
+        }
+
+        let kind = get_source_kind(Path::new(&signed_tree.tree.source));
+        if signed_tree.fingerprint != get_fingerprint(self.config, kind) {
+            return CacheEntryStatus::Foreign;
+        }
+
+        let expected_name = get_cache_entry_name(
+            self.config,
+            &signed_tree.tree.source,
+            signed_tree.fingerprint,
+            signed_tree.signature,
+        );
+        if path.file_name() != Some(expected_name.as_ref()) {
+            return CacheEntryStatus::Foreign;
+        }
+
+        // content addressed entries don't belong to a single source, so they can't go stale.
+        if self.config.cache_key == CacheKey::Origin {
+            let source_path = self.config.root.join(&signed_tree.tree.source);
+            match fs::read_to_string(source_path) {
+                Ok(content) if self.config.hasher.hash(&content) == signed_tree.signature => {}
+                _ => return CacheEntryStatus::Stale,
+            }
+        }
+
+        CacheEntryStatus::Valid
+    }
+
+    fn walk(&self, repair: bool) -> Result<CacheVerification, Error> {
+        let mut verification = CacheVerification::default();
+
+        let cache = match &self.config.cache {
+            Some(cache) => cache,
+            None => return Ok(verification),
+        };
+
+        if !cache.is_dir() {
+            return Err(Error::invalid_path("must be a cache directory.").with_path(cache));
+        }
+
+        for entry in fs::read_dir(cache).map_err(|error| Error::from(error).with_path(cache))? {
+            let path = entry
+                .map_err(|error| Error::from(error).with_path(cache))?
+                .path();
+            let status = self.verify_entry(&path);
+
+            if repair && status != CacheEntryStatus::Valid && path.is_file() {
+                fs::remove_file(&path).map_err(|error| Error::from(error).with_path(&path))?;
+                verification.removed.push(path.clone());
+            }
+
+            match status {
+                CacheEntryStatus::Valid => verification.valid.push(path),
+                CacheEntryStatus::Corrupt => verification.corrupt.push(path),
+                CacheEntryStatus::Stale => verification.stale.push(path),
+                CacheEntryStatus::Foreign => verification.foreign.push(path),
+            }
+        }
+
+        Ok(verification)
+    }
+}
+
+impl CacheVerification {
+    pub fn is_healthy(&self) -> bool {
+        self.corrupt.is_empty() && self.stale.is_empty() && self.foreign.is_empty()
+    }
+}
+
+impl Display for CacheVerification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} valid, {} corrupt, {} stale, {} foreign, {} removed",
+            self.valid.len(),
+            self.corrupt.len(),
+            self.stale.len(),
+            self.foreign.len(),
+            self.removed.len(),
+        )
+    }
+}
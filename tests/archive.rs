@@ -0,0 +1,88 @@
+use std::env;
+use std::fs;
+
+use ara_forest::archive::CacheArchiver;
+use ara_forest::archive::CacheManifest;
+use ara_forest::config::CacheKey;
+use ara_forest::config::Config;
+use ara_forest::error::Error;
+use ara_forest::Parser;
+use tar::Builder;
+use tar::EntryType;
+use tar::Header;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+#[test]
+fn test_exporting_and_importing_cache_archive() {
+    let exported_cache = env::temp_dir().join("ara-forest-exported-cache");
+    let imported_cache = env::temp_dir().join("ara-forest-imported-cache");
+    let archive = env::temp_dir().join("ara-forest-cache.tar");
+    let _ = fs::remove_dir_all(&exported_cache);
+    let _ = fs::remove_dir_all(&imported_cache);
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_cache_directory(exported_cache.to_string_lossy());
+    Parser::new(&config).parse().unwrap();
+
+    let exported = CacheArchiver::new(&config)
+        .export_to_file(&archive)
+        .unwrap();
+    assert_eq!(exported, 4);
+
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_cache_directory(imported_cache.to_string_lossy());
+    let imported = CacheArchiver::new(&config)
+        .import_from_file(&archive)
+        .unwrap();
+    assert_eq!(imported, 4);
+
+    let stats = Parser::new(&config).parse().unwrap().stats;
+    assert_eq!(stats.cache_hits, 4);
+
+    let config = config.with_cache_key(CacheKey::Content);
+    let result = CacheArchiver::new(&config).import_from_file(&archive);
+    assert!(
+        matches!(result, Err(Error::ArchiveError { .. })),
+        "Expected an ArchiveError error, but got something else",
+    );
+}
+
+#[test]
+fn test_importing_cache_archive_with_symlink_entry() {
+    let cache = env::temp_dir().join("ara-forest-symlink-cache");
+    let _ = fs::remove_dir_all(&cache);
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_cache_directory(cache.to_string_lossy());
+
+    let mut builder = Builder::new(Vec::new());
+    let manifest = CacheManifest::from_config(&config).to_string();
+    let mut header = Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, "manifest", manifest.as_bytes())
+        .unwrap();
+
+    let mut header = Header::new_gnu();
+    header.set_entry_type(EntryType::Symlink);
+    header.set_size(0);
+    builder
+        .append_link(&mut header, "entries/foo.ara.cache", "/etc/passwd")
+        .unwrap();
+    let archive = builder.into_inner().unwrap();
+
+    let result = CacheArchiver::new(&config).import(archive.as_slice());
+    assert!(
+        matches!(result, Err(Error::ArchiveError { .. })),
+        "Expected an ArchiveError error, but got something else",
+    );
+    assert!(fs::symlink_metadata(cache.join("foo.ara.cache")).is_err());
+}
@@ -0,0 +1,31 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use walkdir::WalkDir;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+/// Creates an empty temporary project, and copies the given files and directories of
+/// `project-a` into it, relative to its root.
+pub fn create_project(name: &str, paths: &[&str]) -> PathBuf {
+    let root = env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+
+    let project = PathBuf::from(format!("{MANIFEST_DIR}/tests/examples/project-a"));
+    for path in paths {
+        for entry in WalkDir::new(project.join(path)) {
+            let entry = entry.unwrap();
+            let path = root.join(entry.path().strip_prefix(&project).unwrap());
+            if entry.file_type().is_dir() {
+                fs::create_dir_all(path).unwrap();
+            } else {
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::copy(entry.path(), path).unwrap();
+            }
+        }
+    }
+
+    root
+}
@@ -0,0 +1,201 @@
+use std::fs;
+use std::path::PathBuf;
+
+use ara_forest::config::CacheKey;
+use ara_forest::config::Config;
+use ara_forest::error::Error;
+use ara_forest::Parser;
+
+use crate::common::create_project;
+
+mod common;
+
+#[test]
+fn test_loading_config_from_ara_toml() {
+    let root = create_project("ara-forest-config-toml-project", &["src", "vendor"]);
+    fs::write(
+        root.join("ara.toml"),
+        r#"
+source = "src"
+definitions = ["vendor/std-bar/definitions", "vendor/std-foo/definitions"]
+exclude = ["src/Foo/**"]
+cache = ".cache"
+cache-key = "content"
+threads = 2
+serializer = "json"
+
+[logging]
+level = "error"
+"#,
+    )
+    .unwrap();
+
+    let config = Config::discover(root.to_string_lossy()).unwrap();
+    assert_eq!(config.source, PathBuf::from("src"));
+    assert_eq!(config.definitions.len(), 2);
+    assert_eq!(config.cache, Some(root.join(".cache")));
+    assert_eq!(config.cache_key, CacheKey::Content);
+    assert_eq!(config.threads, 2);
+    assert_eq!(config.serializer.name(), "json");
+    assert!(config.logger.is_some());
+
+    // src/Foo holds two of the four sources.
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.tree.trees.len(), 4);
+
+    let config = Config::from_file(root.join("ara.toml")).unwrap();
+    assert_eq!(config.root, root);
+}
+
+#[test]
+fn test_loading_config_from_composer_json() {
+    let root = create_project("ara-forest-config-composer-project", &["src", "vendor"]);
+    fs::write(
+        root.join("composer.json"),
+        r#"{
+    "name": "ara/project",
+    "extra": {
+        "ara": {
+            "source": "src",
+            "include": ["src/Foo/**"]
+        }
+    }
+}"#,
+    )
+    .unwrap();
+
+    let config = Config::discover(root.to_string_lossy()).unwrap();
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.tree.trees.len(), 2);
+
+    // a composer.json without an `extra.ara` section is ignored.
+    fs::write(root.join("composer.json"), r#"{"name": "ara/project"}"#).unwrap();
+    let config = Config::discover(root.to_string_lossy()).unwrap();
+    assert_eq!(config.source, PathBuf::from(""));
+}
+
+#[test]
+fn test_loading_config_with_unknown_keys() {
+    let root = create_project("ara-forest-config-invalid-project", &["src", "vendor"]);
+
+    fs::write(root.join("ara.toml"), "sources = \"src\"\n").unwrap();
+    match Config::discover(root.to_string_lossy()) {
+        Err(Error::ConfigError { message, .. }) => {
+            assert!(message.contains("unknown field `sources`"))
+        }
+        _ => panic!("expected a config error."),
+    }
+
+    fs::write(root.join("ara.toml"), "serializer = \"yaml\"\n").unwrap();
+    match Config::discover(root.to_string_lossy()) {
+        Err(Error::ConfigError { message, .. }) => {
+            assert!(message.contains("unknown serializer `yaml`"))
+        }
+        _ => panic!("expected a config error."),
+    }
+
+    fs::remove_file(root.join("ara.toml")).unwrap();
+    fs::write(
+        root.join("composer.json"),
+        r#"{"extra": {"ara": {"logging": {"colour": true}}}}"#,
+    )
+    .unwrap();
+    match Config::discover(root.to_string_lossy()) {
+        Err(Error::ConfigError { message, .. }) => {
+            assert!(message.contains("unknown field `colour`"))
+        }
+        _ => panic!("expected a config error."),
+    }
+}
+
+#[test]
+fn test_discovering_vendor_definitions() {
+    let root = create_project("ara-forest-config-vendor-project", &["src", "vendor"]);
+
+    // without `installed.json`, every package manifest is read.
+    let config = Config::new(root.to_string_lossy())
+        .with_source("src")
+        .with_vendor_definitions()
+        .unwrap();
+    assert_eq!(
+        config.definitions,
+        vec![
+            PathBuf::from("vendor/std-bar/definitions"),
+            PathBuf::from("vendor/std-foo/definitions"),
+        ]
+    );
+
+    fs::create_dir_all(root.join("vendor/composer")).unwrap();
+    fs::write(
+        root.join("vendor/composer/installed.json"),
+        r#"{
+    "packages": [
+        {"name": "ara/std-foo", "install-path": "../std-foo", "extra": {"ara": {"definitions": "definitions"}}},
+        {"name": "psl/psl", "install-path": "../psl/psl"}
+    ]
+}"#,
+    )
+    .unwrap();
+    fs::write(
+        root.join("ara.toml"),
+        "source = \"src\"\nvendor-definitions = true\n",
+    )
+    .unwrap();
+
+    let config = Config::discover(root.to_string_lossy()).unwrap();
+    assert_eq!(
+        config.definitions,
+        vec![PathBuf::from("vendor/std-foo/definitions")]
+    );
+
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.tree.trees.len(), 5);
+}
+
+#[test]
+fn test_validating_config() {
+    let root = create_project("ara-forest-config-validation-project", &["src", "vendor"]);
+    fs::write(root.join("cache"), "not a directory").unwrap();
+
+    let config = Config::new(root.to_string_lossy())
+        .with_source("lib")
+        .with_definitions(vec!["./vendor", "vendor/std-foo/definitions"])
+        .with_cache_directory("cache")
+        .with_threads(0);
+
+    let report = config.validate().unwrap_err();
+    let messages = report
+        .issues
+        .iter()
+        .map(|issue| issue.message.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(messages.len(), 4);
+    assert!(messages[0].starts_with("source directory"));
+    assert!(messages[1].starts_with("definitions directory (vendor) overlaps"));
+    assert_eq!(messages[2], "the number of threads must be at least 1.");
+    assert!(messages[3].ends_with("is not a directory."));
+
+    // problems are reported before parsing starts.
+    assert_eq!(Parser::new(&config).parse().unwrap_err(), report);
+
+    // an empty source directory is only a warning, and doesn't overlap with definitions.
+    let config = Config::new(root.to_string_lossy())
+        .with_definitions(vec!["vendor/std-foo/definitions"])
+        .with_cache_directory(".cache/nested");
+    assert!(config.validate().is_ok());
+
+    // the cache directory is checked for writability without leaving files behind.
+    assert!(!root.join(".cache").exists());
+    assert!(!fs::read_dir(&root).unwrap().any(|entry| entry
+        .unwrap()
+        .file_name()
+        .to_string_lossy()
+        .ends_with(".tmp")));
+
+    let config = Config::new(root.to_string_lossy())
+        .with_source(".")
+        .with_definitions(vec!["./", "vendor"]);
+    assert!(config.validate().unwrap_err().issues[0]
+        .message
+        .starts_with("definitions directory (.) overlaps"));
+}
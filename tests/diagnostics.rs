@@ -0,0 +1,138 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+
+use ara_forest::config::Config;
+use ara_forest::diagnostics::to_sarif;
+use ara_forest::diagnostics::DiagnosticFormat;
+use ara_forest::diagnostics::DiagnosticsCollector;
+use ara_forest::diagnostics::Position;
+use ara_forest::Parser;
+use ara_reporting::issue::IssueSeverity;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+#[test]
+fn test_collecting_diagnostics() {
+    let config = Config::new(format!("{MANIFEST_DIR}/tests/examples/project-b")).with_source("src");
+    let report = Parser::new(&config).parse().unwrap_err();
+
+    let diagnostics = DiagnosticsCollector::new(&config).collect(&report);
+    assert_eq!(diagnostics.len(), 1);
+
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.severity, IssueSeverity::Error);
+    assert_eq!(diagnostic.code.as_deref(), Some("P0011"));
+    assert_eq!(diagnostic.file.as_deref(), Some("src/Bar/bar.ara"));
+
+    let span = diagnostic.span.unwrap();
+    assert_eq!((span.from, span.to), (30, 32));
+    assert_eq!(
+        span.start,
+        Position {
+            line: 1,
+            column: 31
+        }
+    );
+    assert_eq!(
+        span.end,
+        Position {
+            line: 1,
+            column: 33
+        }
+    );
+
+    let sarif: serde_json::Value = serde_json::from_str(&to_sarif(&diagnostics)).unwrap();
+    assert_eq!(sarif["version"], "2.1.0");
+
+    let result = &sarif["runs"][0]["results"][0];
+    assert_eq!(result["ruleId"], "P0011");
+    assert_eq!(result["level"], "error");
+
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "src/Bar/bar.ara");
+    assert_eq!(location["region"]["startLine"], 1);
+    assert_eq!(location["region"]["startColumn"], 31);
+}
+
+#[test]
+fn test_reporting_diagnostics_from_cli() {
+    let parse = |project: &str, format: &str| {
+        Command::new(env!("CARGO_BIN_EXE_ara-forest"))
+            .args([
+                "parse",
+                &format!("{MANIFEST_DIR}/tests/examples/{project}"),
+                "--source",
+                "src",
+                "--format",
+                format,
+            ])
+            .output()
+            .unwrap()
+    };
+
+    let output = parse("project-b", "json");
+    assert!(!output.status.success());
+
+    let diagnostics: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(diagnostics[0]["file"], "src/Bar/bar.ara");
+    assert_eq!(diagnostics[0]["span"]["start"]["line"], 1);
+
+    // a clean project still produces a log, so code scanning can clear previous results.
+    let output = parse("project-a", "sarif");
+    assert!(output.status.success());
+
+    let sarif: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(sarif["runs"][0]["results"].as_array().unwrap().is_empty());
+
+    assert!(!parse("project-a", "xml").status.success());
+}
+
+#[test]
+fn test_reporting_every_broken_file() {
+    let root = env::temp_dir().join("ara-forest-broken-project");
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/a.ara"), "type a = Stringable || b;\n").unwrap();
+    fs::write(root.join("src/b.ara"), "type b = A || b;\n").unwrap();
+    fs::write(root.join("src/c.ara"), "function c(): void {}\n").unwrap();
+
+    let config = Config::new(root.to_string_lossy())
+        .with_source("src")
+        .with_threads(1);
+
+    // diagnosing doesn't stop at the first broken file.
+    let diagnosis = Parser::new(&config).diagnose().unwrap();
+    let (origins, report) = (&diagnosis.origins, &diagnosis.report);
+    assert_eq!(origins, &["src/a.ara", "src/b.ara", "src/c.ara"]);
+    assert_eq!(report.issues.len(), 2);
+
+    let github = DiagnosticFormat::Github.render(&config, origins, report);
+    assert_eq!(github.lines().count(), 2);
+    assert!(github.contains(
+        "::error file=src/a.ara,line=1,col=21,endLine=1,endColumn=23,title=P0011::unexpected token `||`"
+    ));
+
+    let checkstyle = DiagnosticFormat::Checkstyle.render(&config, origins, report);
+    assert!(checkstyle.contains(r#"<file name="src/a.ara">"#));
+    assert!(checkstyle.contains(
+        r#"<error line="1" column="12" severity="error" message="unexpected token `||`, expected `;`" source="P0011"/>"#
+    ));
+    assert!(checkstyle.contains(r#"<file name="src/c.ara"/>"#));
+
+    let junit = DiagnosticFormat::Junit.render(&config, origins, report);
+    assert!(junit.contains(r#"<testsuite name="ara_forest" tests="3" failures="2">"#));
+    assert!(junit.contains(
+        r#"<failure message="unexpected token `||`, expected `;`" type="P0011">src/b.ara:1:12: "#
+    ));
+    assert!(junit.contains(r#"<testcase name="src/c.ara" classname="ara_forest"/>"#));
+
+    // a cache warmup carries the parsed origins as well.
+    let config = config.with_cache_directory(".cache");
+    let warmup = Parser::new(&config).warm_cache().unwrap();
+    assert_eq!(&warmup.origins, origins);
+    assert_eq!(
+        DiagnosticFormat::Junit.render(&config, &warmup.origins, &warmup.report),
+        junit
+    );
+}
@@ -0,0 +1,72 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+
+use ara_forest::config::Config;
+use ara_forest::error::Error;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+// environment variables are shared by every test of this binary, keep them in a single test.
+#[test]
+fn test_overriding_config_with_env() {
+    let config = Config::new("root")
+        .with_cache_directory(".cache")
+        .with_threads(4);
+
+    env::set_var("ARA_FOREST_CACHE_DIR", ".ci-cache");
+    env::set_var("ARA_FOREST_THREADS", "2");
+    env::set_var("ARA_FOREST_LOG", "debug");
+    let config = config.with_env_overrides().unwrap();
+    assert_eq!(config.cache.as_deref(), Some("root/.ci-cache".as_ref()));
+    assert_eq!(config.threads, 2);
+    assert!(config.logger.is_some());
+
+    // values set after the overrides take precedence.
+    let config = config.with_threads(8);
+    assert_eq!(config.threads, 8);
+
+    env::set_var("ARA_FOREST_NO_CACHE", "true");
+    let config = config.with_env_overrides().unwrap();
+    assert!(config.cache.is_none());
+
+    env::set_var("ARA_FOREST_THREADS", "many");
+    match Config::new("root").with_env_overrides() {
+        Err(Error::ConfigError { message, .. }) => assert!(message.contains("`many` given")),
+        _ => panic!("expected a config error."),
+    }
+
+    for name in [
+        "ARA_FOREST_CACHE_DIR",
+        "ARA_FOREST_THREADS",
+        "ARA_FOREST_LOG",
+        "ARA_FOREST_NO_CACHE",
+    ] {
+        env::remove_var(name);
+    }
+}
+
+#[test]
+fn test_overriding_cli_config_with_env() {
+    let cache = env::temp_dir().join("ara-forest-env-cli-cache");
+    let _ = fs::remove_dir_all(&cache);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ara-forest"))
+        .args([
+            "parse",
+            &format!("{MANIFEST_DIR}/tests/examples/project-a"),
+            "--source",
+            "src",
+        ])
+        .env("ARA_FOREST_CACHE_DIR", &cache)
+        // set by the other test of this binary, which may be running concurrently.
+        .env_remove("ARA_FOREST_THREADS")
+        .env_remove("ARA_FOREST_LOG")
+        .env_remove("ARA_FOREST_LOG_FORMAT")
+        .env_remove("ARA_FOREST_NO_CACHE")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 4);
+}
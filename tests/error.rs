@@ -0,0 +1,77 @@
+use std::env;
+use std::error::Error as StdError;
+use std::fs;
+use std::path::Path;
+
+use ara_forest::config::Config;
+use ara_forest::error::Error;
+use ara_forest::serializer::ArchivedTree;
+use ara_forest::source::SourceFilesCollector;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+#[test]
+fn test_io_error_has_path_and_source() {
+    let path = format!("{MANIFEST_DIR}/tests/examples/project-a/missing.toml");
+
+    let error = Config::from_file(&path).unwrap_err();
+    assert!(matches!(error, Error::IoError { .. }));
+    assert_eq!(error.path(), Some(Path::new(&path)));
+    assert!(error.to_string().contains(&format!("({path})")));
+
+    let source = error.source().unwrap();
+    assert_eq!(
+        source.downcast_ref::<std::io::Error>().unwrap().kind(),
+        std::io::ErrorKind::NotFound
+    );
+}
+
+#[test]
+fn test_attaching_path_to_error() {
+    let error = ArchivedTree::from_bytes(b"not an entry").unwrap_err();
+    assert!(matches!(error, Error::DeserializeError { .. }));
+    assert_eq!(error.path(), None);
+
+    let error = error.with_path(".cache/1.ara.cache");
+    assert_eq!(error.path(), Some(Path::new(".cache/1.ara.cache")));
+    assert_eq!(
+        error.to_string(),
+        "deserialize error (.cache/1.ara.cache): invalid archived cache entry header"
+    );
+
+    // the first path attached is kept.
+    let error = error.with_path("other.ara.cache");
+    assert_eq!(error.path(), Some(Path::new(".cache/1.ara.cache")));
+}
+
+#[test]
+fn test_config_and_invalid_path_errors_have_path() {
+    let path = env::temp_dir().join("ara-forest-invalid-config.toml");
+    fs::write(&path, "threads = \"many\"\n").unwrap();
+
+    let error = Config::from_file(&path).unwrap_err();
+    assert!(matches!(error, Error::ConfigError { .. }));
+    assert_eq!(error.path(), Some(path.as_path()));
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = Config::new(&root).with_source("src/foo.ara");
+    let error = SourceFilesCollector::new(&config).collect().unwrap_err();
+    assert!(matches!(error, Error::InvalidPath { .. }));
+    assert_eq!(
+        error.path(),
+        Some(Path::new(&format!("{root}/src/foo.ara")))
+    );
+}
+
+#[test]
+fn test_error_converts_into_boxed_error() {
+    fn load(path: &str) -> Result<Config, Box<dyn StdError + Send + Sync>> {
+        Ok(Config::from_file(path)?)
+    }
+
+    let error = load(&format!(
+        "{MANIFEST_DIR}/tests/examples/project-a/missing.toml"
+    ))
+    .unwrap_err();
+    assert!(error.downcast_ref::<Error>().is_some());
+}
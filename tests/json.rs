@@ -0,0 +1,65 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+
+use ara_forest::config::Config;
+use ara_forest::serializer::JsonSerializer;
+use ara_forest::Parser;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+#[test]
+fn test_parsing_with_json_cache() {
+    let cache = env::temp_dir().join("ara-forest-json-cache");
+    let _ = fs::remove_dir_all(&cache);
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_cache_directory(cache.to_string_lossy())
+        .with_serializer(JsonSerializer::new());
+
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.stats.cache_misses_missing_entry, 4);
+
+    for entry in fs::read_dir(&cache).unwrap() {
+        let entry: serde_json::Value =
+            serde_json::from_slice(&fs::read(entry.unwrap().path()).unwrap()).unwrap();
+
+        assert!(entry["content"].is_string());
+        assert!(entry["tree"]["source"].as_str().unwrap().starts_with("src"));
+        assert!(entry["tree"]["definitions"]["definitions"].is_array());
+    }
+
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.stats.cache_hits, 4);
+    assert_eq!(forest.stats.deserialize_failures, 0);
+}
+
+#[test]
+fn test_dumping_tree_as_json() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ara-forest"))
+        .args([
+            "dump",
+            &format!("{MANIFEST_DIR}/tests/examples/project-a/src/foo.ara"),
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+
+    let dump: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(dump["source"].as_str().unwrap().ends_with("foo.ara"));
+    assert!(dump["definitions"]["definitions"].is_array());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ara-forest"))
+        .args([
+            "dump",
+            &format!("{MANIFEST_DIR}/tests/examples/project-b/src/Bar/bar.ara"),
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unexpected token `||`"));
+}
@@ -1,8 +1,21 @@
 use std::env;
+use std::fs;
 
+use ara_forest::config::CacheKey;
 use ara_forest::config::Config;
+use ara_forest::error::Error;
 use ara_forest::logger::{LogLevel, Logger};
+use ara_forest::serializer::ArchivedSerializer;
+use ara_forest::serializer::BincodeSerializer;
+use ara_forest::serializer::Compression;
+use ara_forest::serializer::Serializer;
+use ara_forest::tree::SignedTree;
 use ara_forest::Parser;
+use ara_source::source::SourceKind;
+
+use crate::common::create_project;
+
+mod common;
 
 const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
 
@@ -19,10 +32,18 @@ fn test_parsing_project_a_with_log() {
         .with_cache_directory(".cache")
         .with_logger(Logger::new().with_level(LogLevel::Debug));
 
+    // installing the logger more than once is a no-op.
+    config.init_logger().unwrap();
+    config.init_logger().unwrap();
+
     let forest = Parser::new(&config).parse().unwrap();
 
     assert_eq!(forest.source.sources.len(), 6);
     assert_eq!(forest.tree.trees.len(), 6);
+
+    // parsing doesn't install the logger, so it can run again in the same process.
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.tree.trees.len(), 6);
 }
 
 #[test]
@@ -54,3 +75,514 @@ fn test_parsing_project_with_parse_error() {
         .message
         .contains("unexpected token `||`"));
 }
+
+#[test]
+fn test_parsing_project_with_several_parse_errors() {
+    let root = create_project("ara-forest-several-errors-project", &["src"]);
+    fs::write(
+        root.join("src/Foo/broken.ara"),
+        "type a = Stringable || b;\n",
+    )
+    .unwrap();
+    fs::write(root.join("src/Bar/broken.ara"), "type b = A || b;\n").unwrap();
+
+    // every broken file is reported, not only the first one, regardless of how files are
+    // split between threads.
+    for threads in [1, 4] {
+        let config = Config::new(root.to_string_lossy())
+            .with_source("src")
+            .with_threads(threads);
+
+        let report = Parser::new(&config).parse().unwrap_err();
+        let mut files = report
+            .issues
+            .iter()
+            .filter_map(|issue| issue.source.as_ref().map(|(file, _, _)| file.as_str()))
+            .collect::<Vec<_>>();
+        files.sort_unstable();
+
+        assert_eq!(files, ["src/Bar/broken.ara", "src/Foo/broken.ara"]);
+    }
+}
+
+#[test]
+fn test_parsing_with_content_addressed_cache() {
+    let cache = env::temp_dir().join("ara-forest-content-addressed-cache");
+    let moved = env::temp_dir().join("ara-forest-content-addressed-project");
+    let _ = fs::remove_dir_all(&cache);
+    let _ = fs::remove_dir_all(&moved);
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_cache_directory(cache.to_string_lossy())
+        .with_cache_key(CacheKey::Content);
+
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.tree.trees.len(), 4);
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 4);
+
+    fs::create_dir_all(moved.join("lib/Moved")).unwrap();
+    fs::copy(
+        format!("{root}/src/foo.ara"),
+        moved.join("lib/Moved/foo.ara"),
+    )
+    .unwrap();
+
+    let config = Config::new(moved.to_string_lossy())
+        .with_source("lib")
+        .with_cache_directory(cache.to_string_lossy())
+        .with_cache_key(CacheKey::Content);
+
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.tree.trees.len(), 1);
+    assert_eq!(forest.tree.trees[0].source, "lib/Moved/foo.ara");
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 4);
+
+    // the shared entry isn't rewritten with the stamp of each source it's loaded for.
+    assert_eq!(forest.stats.cache_hits, 1);
+    assert_eq!(forest.stats.bytes_written, 0);
+}
+
+#[test]
+fn test_parsing_with_compressed_cache() {
+    let plain_cache = env::temp_dir().join("ara-forest-plain-cache");
+    let compressed_cache = env::temp_dir().join("ara-forest-compressed-cache");
+    let _ = fs::remove_dir_all(&plain_cache);
+    let _ = fs::remove_dir_all(&compressed_cache);
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_cache_directory(plain_cache.to_string_lossy());
+    Parser::new(&config).parse().unwrap();
+
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_cache_directory(compressed_cache.to_string_lossy())
+        .with_compression(Compression::Lz4);
+    Parser::new(&config).parse().unwrap();
+
+    let size = |cache: &std::path::Path| -> u64 {
+        fs::read_dir(cache)
+            .unwrap()
+            .map(|entry| entry.unwrap().metadata().unwrap().len())
+            .sum()
+    };
+    let compressed_size = size(&compressed_cache);
+    assert!(compressed_size < size(&plain_cache));
+
+    // compressed and uncompressed entries can be read regardless of the configured compression.
+    for compression in [Compression::None, Compression::Lz4] {
+        let config = Config::new(&root)
+            .with_source("src")
+            .with_cache_directory(compressed_cache.to_string_lossy())
+            .with_compression(compression);
+        let forest = Parser::new(&config).parse().unwrap();
+
+        assert_eq!(forest.tree.trees.len(), 4);
+        assert_eq!(size(&compressed_cache), compressed_size);
+    }
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_parsing_with_zstd_compressed_cache() {
+    let cache = env::temp_dir().join("ara-forest-zstd-cache");
+    let _ = fs::remove_dir_all(&cache);
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = |compression| {
+        Config::new(&root)
+            .with_source("src")
+            .with_cache_directory(cache.to_string_lossy())
+            .with_compression(compression)
+    };
+    Parser::new(config(Compression::Zstd(3))).parse().unwrap();
+
+    let forest = Parser::new(config(Compression::None)).parse().unwrap();
+    assert_eq!(forest.stats.cache_hits, 4);
+    assert_eq!(forest.stats.deserialize_failures, 0);
+}
+
+#[test]
+fn test_parsing_with_compression_set_before_serializer() {
+    let cache = env::temp_dir().join("ara-forest-compressed-archived-cache");
+    let _ = fs::remove_dir_all(&cache);
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_cache_directory(cache.to_string_lossy())
+        .with_compression(Compression::Lz4)
+        .with_serializer(ArchivedSerializer::new());
+    Parser::new(&config).parse().unwrap();
+
+    for entry in fs::read_dir(&cache).unwrap() {
+        let data = fs::read(entry.unwrap().path()).unwrap();
+
+        // the entry is compressed, so the archived header can't be read in place.
+        assert!(config.serializer.header(&data).is_none());
+        assert!(config.entry_serializer().deserialize(&data).is_ok());
+    }
+
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.stats.cache_hits, 4);
+}
+
+#[test]
+fn test_parsing_with_archived_cache() {
+    let root = create_project("ara-forest-archived-project", &["src/foo.ara"]);
+
+    let source = root.join("src/foo.ara");
+    let original = fs::read_to_string(&source).unwrap();
+
+    let config = Config::new(root.to_string_lossy())
+        .with_source("src")
+        .with_cache_directory(".cache")
+        .with_serializer(ArchivedSerializer::new());
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.stats.cache_misses_missing_entry, 1);
+
+    let entry = fs::read_dir(root.join(".cache"))
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let data = fs::read(entry).unwrap();
+    let header = config.serializer.header(&data).unwrap();
+    assert_eq!(header.signature, config.hasher.hash(&original));
+    assert_eq!(
+        config.serializer.deserialize(&data).unwrap().content,
+        original
+    );
+
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.stats.cache_hits, 1);
+
+    fs::write(&source, original.replace("$first", "$second")).unwrap();
+    let forest = Parser::new(config.with_strict_hashing(true))
+        .parse()
+        .unwrap();
+    assert_eq!(forest.stats.cache_misses_content_changed, 1);
+}
+
+#[test]
+fn test_parsing_with_modification_time_fast_path() {
+    let root = create_project("ara-forest-fast-path-project", &["src/foo.ara"]);
+
+    let source = root.join("src/foo.ara");
+    let original = fs::read_to_string(&source).unwrap();
+
+    let config = Config::new(root.to_string_lossy())
+        .with_source("src")
+        .with_cache_directory(".cache");
+    Parser::new(&config).parse().unwrap();
+
+    // change the content while keeping the same size and modification time.
+    let modified = fs::metadata(&source).unwrap().modified().unwrap();
+    let changed = original.replace("$first", "$frist");
+    fs::write(&source, &changed).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(&source)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.source.sources[0].content, original);
+
+    let config = config.with_strict_hashing(true);
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.source.sources[0].content, changed);
+
+    // an edited source doesn't read its cache entry again after the fast path rejected it.
+    let entry = fs::read_dir(root.join(".cache"))
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let entry_size = fs::metadata(entry).unwrap().len();
+    fs::write(&source, original.replace("$first", "$second")).unwrap();
+    let stats = Parser::new(config.with_strict_hashing(false))
+        .parse()
+        .unwrap()
+        .stats;
+    assert_eq!(stats.cache_misses_content_changed, 1);
+    assert_eq!(stats.bytes_read, entry_size);
+}
+
+#[test]
+fn test_parsing_reports_cache_stats() {
+    let root = create_project("ara-forest-stats-project", &["src/foo.ara"]);
+
+    let source = root.join("src/foo.ara");
+    let content = fs::read_to_string(&source).unwrap();
+
+    let config = Config::new(root.to_string_lossy())
+        .with_source("src")
+        .with_cache_directory(".cache");
+
+    let stats = Parser::new(&config).parse().unwrap().stats;
+    assert_eq!(stats.files, 1);
+    assert_eq!(stats.cache_hits, 0);
+    assert_eq!(stats.cache_misses_missing_entry, 1);
+    assert!(stats.bytes_written > 0);
+
+    let stats = Parser::new(&config).parse().unwrap().stats;
+    assert_eq!(stats.cache_hits, 1);
+    assert_eq!(stats.cache_misses(), 0);
+    assert_eq!(stats.bytes_written, 0);
+    assert!(stats.bytes_read > 0);
+
+    fs::write(&source, format!("{content}\n")).unwrap();
+    let stats = Parser::new(&config).parse().unwrap().stats;
+    assert_eq!(stats.cache_hits, 0);
+    assert_eq!(stats.cache_misses_content_changed, 1);
+
+    for entry in fs::read_dir(root.join(".cache")).unwrap() {
+        fs::write(entry.unwrap().path(), b"corrupted").unwrap();
+    }
+    let stats = Parser::new(&config).parse().unwrap().stats;
+    assert_eq!(stats.cache_hits, 0);
+    assert_eq!(stats.deserialize_failures, 1);
+}
+
+#[test]
+fn test_parsing_with_read_only_cache() {
+    let root = create_project("ara-forest-read-only-project", &["src/foo.ara"]);
+
+    let source = root.join("src/foo.ara");
+    let content = fs::read_to_string(&source).unwrap();
+
+    let config = Config::new(root.to_string_lossy())
+        .with_source("src")
+        .with_cache_directory(".cache")
+        .with_read_only_cache(true);
+
+    // a missing read-only cache directory is not created.
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.tree.trees.len(), 1);
+    assert!(!root.join(".cache").exists());
+
+    let config = config.with_read_only_cache(false);
+    Parser::new(&config).parse().unwrap();
+
+    let entries = || -> Vec<Vec<u8>> {
+        fs::read_dir(root.join(".cache"))
+            .unwrap()
+            .map(|entry| fs::read(entry.unwrap().path()).unwrap())
+            .collect()
+    };
+    let warm = entries();
+
+    let config = config.with_read_only_cache(true);
+    let stats = Parser::new(&config).parse().unwrap().stats;
+    assert_eq!(stats.cache_hits, 1);
+
+    fs::write(&source, format!("{content}\n")).unwrap();
+    let stats = Parser::new(&config).parse().unwrap().stats;
+    assert_eq!(stats.cache_misses_content_changed, 1);
+    assert_eq!(stats.bytes_written, 0);
+    assert_eq!(entries(), warm);
+}
+
+#[test]
+fn test_parsing_with_limited_cache_evicts_least_recently_used_entries() {
+    let cache = env::temp_dir().join("ara-forest-limited-cache");
+    let _ = fs::remove_dir_all(&cache);
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_definitions(vec![
+            format!("vendor/std-bar/definitions"),
+            format!("vendor/std-foo/definitions"),
+        ])
+        .with_cache_directory(cache.to_string_lossy());
+    Parser::new(&config).parse().unwrap();
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 6);
+
+    // only the sources are used in this run, so the definitions are evicted.
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_cache_directory(cache.to_string_lossy())
+        .with_max_cache_entries(4);
+    let stats = Parser::new(&config).parse().unwrap().stats;
+    assert_eq!(stats.cache_hits, 4);
+    assert_eq!(stats.evicted_entries, 2);
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 4);
+
+    let config = config.with_max_cache_size(0);
+    let stats = Parser::new(&config).parse().unwrap().stats;
+    assert_eq!(stats.cache_hits, 4);
+    assert_eq!(stats.evicted_entries, 4);
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 0);
+}
+
+#[test]
+fn test_warming_cache() {
+    let cache = env::temp_dir().join("ara-forest-warm-cache");
+    let _ = fs::remove_dir_all(&cache);
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_definitions(vec![
+            format!("vendor/std-bar/definitions"),
+            format!("vendor/std-foo/definitions"),
+        ])
+        .with_cache_directory(cache.to_string_lossy());
+
+    let warmup = Parser::new(&config).warm_cache().unwrap();
+    assert_eq!(warmup.stats.files, 6);
+    assert_eq!(warmup.stats.cache_misses_missing_entry, 6);
+    assert!(warmup.report.issues.is_empty());
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 6);
+
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.stats.cache_hits, 6);
+
+    let report = Parser::new(Config::new(&root).with_source("src"))
+        .warm_cache()
+        .unwrap_err();
+    assert!(report.issues[0].message.starts_with("config error"));
+
+    let report = Parser::new(config.with_read_only_cache(true))
+        .warm_cache()
+        .unwrap_err();
+    assert!(report.issues[0].message.starts_with("config error"));
+}
+
+#[test]
+fn test_warming_archived_cache_without_decoding() {
+    let root = create_project("ara-forest-warm-archived-project", &["src/foo.ara"]);
+
+    let config = Config::new(root.to_string_lossy())
+        .with_source("src")
+        .with_cache_directory(".cache")
+        .with_serializer(ArchivedSerializer::new());
+
+    let warmup = Parser::new(&config).warm_cache().unwrap();
+    assert_eq!(warmup.stats.cache_misses_missing_entry, 1);
+
+    // truncating the encoded tree leaves the header intact, up to date entries are
+    // only checked against their header when warming, so the damage goes unnoticed.
+    let entry = fs::read_dir(root.join(".cache"))
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let mut data = fs::read(&entry).unwrap();
+    data.pop();
+    fs::write(&entry, data).unwrap();
+
+    let warmup = Parser::new(&config).warm_cache().unwrap();
+    assert_eq!(warmup.stats.cache_hits, 1);
+    assert_eq!(warmup.stats.bytes_written, 0);
+
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.stats.cache_hits, 0);
+    assert_eq!(forest.stats.files, 1);
+}
+
+#[test]
+fn test_warming_cache_with_parse_error() {
+    let cache = env::temp_dir().join("ara-forest-warm-cache-with-error");
+    let _ = fs::remove_dir_all(&cache);
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-b");
+    let config = Config::new(root)
+        .with_source("src")
+        .with_cache_directory(cache.to_string_lossy());
+
+    let warmup = Parser::new(&config).warm_cache().unwrap();
+    assert_eq!(warmup.stats.files, 2);
+    assert_eq!(fs::read_dir(&cache).unwrap().count(), 1);
+    assert!(warmup
+        .report
+        .issues
+        .first()
+        .unwrap()
+        .message
+        .contains("unexpected token `||`"));
+}
+
+struct NamedSerializer(&'static str, BincodeSerializer);
+
+impl Serializer for NamedSerializer {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    fn serialize(&self, signed_tree: &SignedTree) -> Result<Vec<u8>, Error> {
+        self.1.serialize(signed_tree)
+    }
+
+    fn deserialize(&self, data: &[u8]) -> Result<SignedTree, Error> {
+        self.1.deserialize(data)
+    }
+}
+
+#[test]
+fn test_parsing_with_changed_configuration_invalidates_cache() {
+    let cache = env::temp_dir().join("ara-forest-fingerprint-cache");
+    let _ = fs::remove_dir_all(&cache);
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = |name| {
+        Config::new(&root)
+            .with_source("src")
+            .with_definitions(vec![
+                "vendor/std-bar/definitions",
+                "vendor/std-foo/definitions",
+            ])
+            .with_cache_directory(cache.to_string_lossy())
+            .with_strict_hashing(true)
+            .with_serializer(NamedSerializer(name, BincodeSerializer::new()))
+    };
+
+    let forest = Parser::new(config("first")).parse().unwrap();
+    assert_eq!(forest.stats.cache_misses_missing_entry, 6);
+    assert_eq!(
+        forest
+            .source
+            .sources
+            .iter()
+            .filter(|source| source.kind == SourceKind::Definition)
+            .count(),
+        2
+    );
+
+    let forest = Parser::new(config("first")).parse().unwrap();
+    assert_eq!(forest.stats.cache_hits, 6);
+
+    // entries written with another serializer are reparsed, even though they can be read.
+    let forest = Parser::new(config("second")).parse().unwrap();
+    assert_eq!(forest.stats.cache_misses_config_changed, 6);
+    assert_eq!(forest.stats.cache_misses_content_changed, 0);
+    assert_eq!(forest.stats.deserialize_failures, 0);
+}
+
+#[test]
+fn test_parsing_in_background_thread() {
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = Config::new(root)
+        .with_source("src")
+        .with_compression(Compression::Lz4);
+
+    let debug = format!("{config:?}");
+    assert!(debug.contains("source: \"src\""));
+    assert!(debug.contains("serializer: \"bincode\""));
+
+    let parser = Parser::new(config.clone().with_threads(1));
+    let forest = std::thread::spawn(move || parser.parse().unwrap())
+        .join()
+        .unwrap();
+
+    assert_eq!(forest.tree.trees.len(), 4);
+}
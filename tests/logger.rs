@@ -0,0 +1,70 @@
+use std::env;
+use std::fs;
+
+use ara_forest::config::Config;
+use ara_forest::logger::LogFormat;
+use ara_forest::logger::LogLevel;
+use ara_forest::logger::Logger;
+use ara_forest::Parser;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+// the logger is global, so it gets a test binary of its own.
+#[test]
+fn test_logging_json_lines() {
+    let cache = env::temp_dir().join("ara-forest-json-log-cache");
+    let log = env::temp_dir().join("ara-forest-json-log.jsonl");
+    let _ = fs::remove_dir_all(&cache);
+
+    let config = Config::new(format!("{MANIFEST_DIR}/tests/examples/project-a"))
+        .with_source("src")
+        .with_cache_directory(cache.to_string_lossy())
+        .with_logger(
+            Logger::new()
+                .with_level(LogLevel::Info)
+                .with_format(LogFormat::Json)
+                .with_file(log.clone()),
+        );
+    config.init_logger().unwrap();
+
+    Parser::new(&config).parse().unwrap();
+    Parser::new(&config).parse().unwrap();
+    log::logger().flush();
+
+    let records = fs::read_to_string(&log)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+
+    for record in &records {
+        for field in ["timestamp", "level", "event", "origin", "cache", "message"] {
+            assert!(record.get(field).is_some(), "missing `{field}` in {record}");
+        }
+
+        let timestamp = record["timestamp"].as_str().unwrap();
+        assert_eq!(timestamp.len(), "2023-01-01T00:00:00.000Z".len());
+        assert!(timestamp.ends_with('Z'));
+    }
+
+    let events = |event: &str| {
+        records
+            .iter()
+            .filter(|record| record["event"] == event)
+            .collect::<Vec<_>>()
+    };
+
+    let writes = events("cache_write");
+    assert_eq!(writes.len(), 4);
+    assert_eq!(writes[0]["level"], "info");
+    assert!(writes[0]["origin"].as_str().unwrap().starts_with("src/"));
+    assert!(writes[0]["cache"].as_str().unwrap().ends_with(".ara.cache"));
+
+    assert_eq!(events("cache_hit").len(), 4);
+
+    let summaries = events("parsed");
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[1]["files"], 4);
+    assert_eq!(summaries[1]["cache_hits"], 4);
+    assert!(summaries[1]["origin"].is_null());
+}
@@ -0,0 +1,163 @@
+#![cfg(feature = "remote")]
+
+use std::collections::HashMap;
+use std::env;
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use tiny_http::Method;
+use tiny_http::Response;
+use tiny_http::Server;
+
+use ara_forest::config::Config;
+use ara_forest::error::Error;
+use ara_forest::remote::HttpRemoteCache;
+use ara_forest::remote::RemoteCache;
+use ara_forest::Parser;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+#[derive(Default)]
+struct Storage {
+    entries: HashMap<String, Vec<u8>>,
+    hits: usize,
+    misses: usize,
+    puts: usize,
+}
+
+fn start_server() -> (String, Arc<Mutex<Storage>>) {
+    start_server_on("127.0.0.1:0")
+}
+
+fn start_server_on(address: &str) -> (String, Arc<Mutex<Storage>>) {
+    let server = Server::http(address).unwrap();
+    let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+    let storage = Arc::new(Mutex::new(Storage::default()));
+
+    let shared = storage.clone();
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let key = request.url().to_string();
+            let mut storage = shared.lock().unwrap();
+            match request.method() {
+                Method::Get => match storage.entries.get(&key).cloned() {
+                    Some(data) => {
+                        storage.hits += 1;
+                        request.respond(Response::from_data(data)).unwrap();
+                    }
+                    None => {
+                        storage.misses += 1;
+                        request.respond(Response::empty(404)).unwrap();
+                    }
+                },
+                Method::Put => {
+                    let mut data = Vec::new();
+                    request.as_reader().read_to_end(&mut data).unwrap();
+                    storage.entries.insert(key, data);
+                    storage.puts += 1;
+                    request.respond(Response::empty(201)).unwrap();
+                }
+                _ => request.respond(Response::empty(405)).unwrap(),
+            }
+        }
+    });
+
+    (url, storage)
+}
+
+#[test]
+fn test_parsing_project_a_with_remote_cache() {
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let (url, storage) = start_server();
+
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_definitions(vec![
+            format!("vendor/std-bar/definitions"),
+            format!("vendor/std-foo/definitions"),
+        ])
+        .with_remote_cache(HttpRemoteCache::new(&url));
+
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.tree.trees.len(), 6);
+
+    {
+        let storage = storage.lock().unwrap();
+        assert_eq!(storage.misses, 6);
+        assert_eq!(storage.puts, 6);
+        assert_eq!(storage.entries.len(), 6);
+    }
+
+    let forest = Parser::new(&config).parse().unwrap();
+    assert_eq!(forest.tree.trees.len(), 6);
+    assert!(forest
+        .tree
+        .trees
+        .iter()
+        .any(|tree| tree.source == "src/Foo/Bar/Baz/baz.ara"));
+
+    let storage = storage.lock().unwrap();
+    assert_eq!(storage.hits, 6);
+    assert_eq!(storage.puts, 6);
+}
+
+#[test]
+fn test_parsing_project_a_with_unreachable_remote_cache() {
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let remote = HttpRemoteCache::with_timeout(format!("http://{address}"), Duration::from_secs(1));
+
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_remote_cache(remote);
+
+    let forest = Parser::new(&config).parse().unwrap();
+
+    assert_eq!(forest.tree.trees.len(), 4);
+}
+
+#[test]
+fn test_remote_cache_error_has_key() {
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let remote = HttpRemoteCache::with_timeout(format!("http://{address}"), Duration::from_secs(1));
+
+    let error = remote.get("1.ara.cache").unwrap_err();
+    assert!(matches!(error, Error::RemoteError { .. }));
+    assert_eq!(error.key(), Some("1.ara.cache"));
+    assert_eq!(error.path(), None);
+    assert!(error
+        .to_string()
+        .starts_with("remote cache error (1.ara.cache): "));
+}
+
+#[test]
+fn test_parsing_with_remote_cache_that_becomes_reachable() {
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let remote = HttpRemoteCache::with_timeout(format!("http://{address}"), Duration::from_secs(1));
+
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_remote_cache(remote);
+    Parser::new(&config).parse().unwrap();
+
+    // the remote cache is shared with the previous parser, but is tried again.
+    let (_, storage) = start_server_on(&address.to_string());
+    Parser::new(&config).parse().unwrap();
+
+    assert_eq!(storage.lock().unwrap().puts, 4);
+}
@@ -1,8 +1,11 @@
 use std::env;
+use std::path::Path;
 
 use ara_forest::config::Config;
 use ara_forest::error::Error;
 use ara_forest::source::SourceFilesCollector;
+use ara_forest::tree::get_source_kind;
+use ara_source::source::SourceKind;
 
 const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
 
@@ -36,7 +39,7 @@ fn test_trying_to_collect_files_in_a_fake_directory() {
     let result = SourceFilesCollector::new(&config).collect();
 
     assert!(
-        matches!(result, Err(Error::InvalidPath(_))),
+        matches!(result, Err(Error::InvalidPath { .. })),
         "Expected an InvalidSource error, but got something else",
     );
 }
@@ -49,7 +52,20 @@ fn test_trying_to_collect_files_in_a_invalid_path() {
     let result = SourceFilesCollector::new(&config).collect();
 
     assert!(
-        matches!(result, Err(Error::InvalidPath(_))),
+        matches!(result, Err(Error::InvalidPath { .. })),
         "Expected an InvalidSource error, but got something else",
     );
 }
+
+#[test]
+fn test_getting_source_kind() {
+    assert_eq!(
+        get_source_kind(Path::new("src/foo.ara")),
+        SourceKind::Script
+    );
+    assert_eq!(
+        get_source_kind(Path::new("vendor/std-foo.d.ara")),
+        SourceKind::Definition
+    );
+    assert_eq!(get_source_kind(Path::new("src/d.ara")), SourceKind::Script);
+}
@@ -0,0 +1,136 @@
+use std::env;
+use std::fs;
+
+use ara_forest::config::Config;
+use ara_forest::serializer::ArchivedSerializer;
+use ara_forest::symbol::SymbolKind;
+use ara_forest::symbol::SymbolNamespace;
+use ara_forest::Forest;
+use ara_forest::Parser;
+use ara_source::source::SourceKind;
+
+use crate::common::create_project;
+
+mod common;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+fn config() -> Config {
+    Config::new(format!("{MANIFEST_DIR}/tests/examples/project-a"))
+        .with_source("src")
+        .with_definitions(vec![
+            "vendor/std-bar/definitions".to_string(),
+            "vendor/std-foo/definitions".to_string(),
+        ])
+}
+
+fn assert_symbols(forest: &Forest) {
+    let function = forest
+        .symbols
+        .get(SymbolNamespace::Function, "Example\\list_directories")
+        .unwrap();
+    assert_eq!(function.origin, "src/foo.ara");
+    assert_eq!(function.kind, SymbolKind::Function);
+    assert_eq!(function.source_kind, SourceKind::Script);
+
+    let content = fs::read_to_string(format!(
+        "{MANIFEST_DIR}/tests/examples/project-a/src/foo.ara"
+    ))
+    .unwrap();
+    assert!(content[function.from..function.to].starts_with("function list_directories("));
+    assert!(content[function.from..function.to].ends_with('}'));
+
+    let definition = forest
+        .symbols
+        .get(SymbolNamespace::Function, "\\strlen")
+        .unwrap();
+    assert_eq!(
+        definition.origin,
+        "vendor/std-bar/definitions/std-bar.d.ara"
+    );
+    assert_eq!(definition.source_kind, SourceKind::Definition);
+
+    let kind = |namespace: SymbolNamespace, name: &str| {
+        forest
+            .symbols
+            .get(namespace, name)
+            .map(|symbol| symbol.kind)
+    };
+    assert_eq!(
+        kind(SymbolNamespace::ClassLike, "Closure"),
+        Some(SymbolKind::Class)
+    );
+    assert_eq!(
+        kind(SymbolNamespace::ClassLike, "ArrayAccess"),
+        Some(SymbolKind::Interface)
+    );
+    assert_eq!(
+        kind(SymbolNamespace::Constant, "PHP_INT_MAX"),
+        Some(SymbolKind::Constant)
+    );
+    assert_eq!(
+        kind(SymbolNamespace::ClassLike, "hello_world"),
+        Some(SymbolKind::TypeAlias)
+    );
+    assert_eq!(kind(SymbolNamespace::Function, "list_directories"), None);
+    assert_eq!(kind(SymbolNamespace::Function, "Closure"), None);
+
+    // every declaration of a name is kept.
+    let declarations = forest.symbols.get_all(SymbolNamespace::Function, "a");
+    assert_eq!(declarations.len(), 2);
+    assert!(declarations[0].from < declarations[1].from);
+}
+
+#[test]
+fn test_indexing_symbols_sharing_a_name() {
+    let root = create_project("ara-forest-symbols-namespaces-project", &["src/foo.ara"]);
+    fs::write(
+        root.join("src/shared.ara"),
+        "function Shared(): void {}\n\nfinal class Shared {}\n\nconst int Shared = 1;\n",
+    )
+    .unwrap();
+
+    let config = Config::new(root.to_string_lossy()).with_source("src");
+    let forest = Parser::new(&config).parse().unwrap();
+
+    for (namespace, kind) in [
+        (SymbolNamespace::Function, SymbolKind::Function),
+        (SymbolNamespace::ClassLike, SymbolKind::Class),
+        (SymbolNamespace::Constant, SymbolKind::Constant),
+    ] {
+        let declarations = forest.symbols.get_all(namespace, "Shared");
+        assert_eq!(declarations.len(), 1);
+        assert_eq!(declarations[0].kind, kind);
+        assert_eq!(declarations[0].origin, "src/shared.ara");
+    }
+}
+
+#[test]
+fn test_indexing_symbols() {
+    let forest = Parser::new(config()).parse().unwrap();
+
+    assert_symbols(&forest);
+}
+
+#[test]
+fn test_indexing_symbols_from_cache() {
+    for (name, config) in [
+        ("bincode", config()),
+        (
+            "archived",
+            config().with_serializer(ArchivedSerializer::new()),
+        ),
+    ] {
+        let cache = env::temp_dir().join(format!("ara-forest-symbols-{name}-cache"));
+        let _ = fs::remove_dir_all(&cache);
+        let config = config.with_cache_directory(cache.to_string_lossy());
+
+        let forest = Parser::new(&config).parse().unwrap();
+        assert_eq!(forest.stats.cache_hits, 0);
+        assert_symbols(&forest);
+
+        let forest = Parser::new(&config).parse().unwrap();
+        assert_eq!(forest.stats.cache_hits, 6);
+        assert_symbols(&forest);
+    }
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "tracing")]
+
+use std::env;
+use std::fmt::Debug;
+use std::fs;
+use std::sync::Mutex;
+
+use tracing::field::Field;
+use tracing::field::Visit;
+use tracing::span::Attributes;
+use tracing::span::Id;
+use tracing::span::Record;
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+use tracing_subscriber::Registry;
+
+use ara_forest::config::Config;
+use ara_forest::Parser;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+// every span is recorded as its name followed by its fields, e.g. `build origin=src/foo.ara`.
+static SPANS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct RecordingLayer;
+
+struct SpanIndex(usize);
+
+struct FieldsVisitor<'a>(&'a mut String);
+
+impl Visit for FieldsVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push_str(&format!(" {}={}", field.name(), value));
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for RecordingLayer {
+    fn on_new_span(&self, attributes: &Attributes<'_>, id: &Id, context: Context<'_, S>) {
+        let mut line = attributes.metadata().name().to_string();
+        attributes.record(&mut FieldsVisitor(&mut line));
+
+        let mut spans = SPANS.lock().unwrap();
+        spans.push(line);
+
+        let span = context.span(id).unwrap();
+        span.extensions_mut().insert(SpanIndex(spans.len() - 1));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, context: Context<'_, S>) {
+        let span = context.span(id).unwrap();
+        let extensions = span.extensions();
+        let index = extensions.get::<SpanIndex>().unwrap().0;
+
+        values.record(&mut FieldsVisitor(&mut SPANS.lock().unwrap()[index]));
+    }
+}
+
+#[test]
+fn test_parsing_emits_spans() {
+    tracing::subscriber::set_global_default(Registry::default().with(RecordingLayer)).unwrap();
+
+    let cache = env::temp_dir().join("ara-forest-tracing-cache");
+    let _ = fs::remove_dir_all(&cache);
+
+    let root = format!("{MANIFEST_DIR}/tests/examples/project-a");
+    let config = Config::new(root)
+        .with_source("src")
+        .with_cache_directory(cache.to_string_lossy());
+
+    Parser::new(&config).parse().unwrap();
+    Parser::new(&config).parse().unwrap();
+
+    let spans = SPANS.lock().unwrap();
+    let find = |prefix: &str| {
+        spans
+            .iter()
+            .filter(|span| span.starts_with(prefix))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(find("parse root=").len(), 2);
+    assert!(find("parse root=")[0].ends_with("files=4"));
+    assert_eq!(find("collect root=").len(), 2);
+    assert!(!find("chunk files=").is_empty());
+    assert_eq!(find("serialize").len(), 4);
+    assert_eq!(find("cache_write").len(), 4);
+    assert_eq!(find("deserialize size=").len(), 4);
+
+    let builds = find("build origin=src/foo.ara");
+    assert_eq!(builds.len(), 2);
+    assert!(builds[0].contains("outcome=miss_missing_entry"));
+    assert!(builds[1].contains("outcome=hit"));
+    assert!(builds[1].contains("duration_us="));
+}
@@ -0,0 +1,66 @@
+use std::fs;
+use std::process::Command;
+
+use ara_forest::config::Config;
+use ara_forest::verify::CacheVerifier;
+use ara_forest::Parser;
+
+use crate::common::create_project;
+
+mod common;
+
+fn create_cached_project(name: &str) -> (String, Config) {
+    let root = create_project(name, &["src/foo.ara", "src/Bar/bar.ara"]);
+
+    let root = root.to_string_lossy().to_string();
+    let config = Config::new(&root)
+        .with_source("src")
+        .with_cache_directory(".cache");
+
+    (root, config)
+}
+
+#[test]
+fn test_verifying_and_repairing_cache() {
+    let (root, config) = create_cached_project("ara-forest-verify-project");
+    Parser::new(&config).parse().unwrap();
+
+    let verification = CacheVerifier::new(&config).verify().unwrap();
+    assert_eq!(verification.valid.len(), 2);
+    assert!(verification.is_healthy());
+
+    let mut entries = fs::read_dir(format!("{root}/.cache"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    entries.sort();
+    fs::write(&entries[0], b"corrupted").unwrap();
+    fs::write(format!("{root}/.cache/notes.txt"), b"foreign").unwrap();
+    fs::write(format!("{root}/src/foo.ara"), "function foo(): void {}").unwrap();
+    fs::write(format!("{root}/src/Bar/bar.ara"), "function bar(): void {}").unwrap();
+
+    let verification = CacheVerifier::new(&config).verify().unwrap();
+    assert_eq!(verification.valid.len(), 0);
+    assert_eq!(verification.corrupt.len(), 1);
+    assert_eq!(verification.stale.len(), 1);
+    assert_eq!(verification.foreign.len(), 1);
+    assert!(verification.removed.is_empty());
+
+    let verification = CacheVerifier::new(&config).repair().unwrap();
+    assert_eq!(verification.removed.len(), 3);
+    assert_eq!(fs::read_dir(format!("{root}/.cache")).unwrap().count(), 0);
+}
+
+#[test]
+fn test_verifying_cache_from_command_line() {
+    let (root, config) = create_cached_project("ara-forest-verify-cli-project");
+    Parser::new(&config).parse().unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ara-forest"))
+        .args(["verify", &root, "--source", "src", "--cache", ".cache"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("2 valid, 0 corrupt"));
+}